@@ -1,389 +1,358 @@
+use std::env;
+use std::ffi::OsString;
 use std::fs;
-use std::path::Path;
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::fs::PermissionsExt;
-use std::io::Write;
-use flate2::Compression;
-use flate2::write::ZlibEncoder;
-use byteorder::{BigEndian, WriteBytesExt};
+use rakke::config::Config;
+use rakke::index::{self, Entry, Index};
+use rakke::objects;
+use rakke::paths;
+use rakke::pathspec;
+use rakke::repository::Repository;
 
-pub fn execute(args: Vec<String>) {
+pub fn execute(args: Vec<OsString>) {
+    let mut file_paths: Vec<Vec<u8>> = Vec::new();
+    let mut pathspec_file: Option<PathBuf> = None;
+    let mut pathspec_file_nul = false;
+    let mut ignore_errors: Option<bool> = None;
+    let mut quiet = false;
+    
+    // Process arguments (skip "add" command itself); paths keep their raw bytes, so options
+    // are only matched when the argument is valid UTF-8
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].to_str().unwrap_or("") {
+            "--pathspec-from-file" => {
+                // File name is given in the next argument
+                i += 1;
+                match args.get(i) {
+                    Some(file) => pathspec_file = Some(PathBuf::from(file)),
+                    None => {
+                        eprintln!("error: option 'pathspec-from-file' requires a value");
+                        std::process::exit(129);
+                    }
+                }
+            }
+            arg if arg.starts_with("--pathspec-from-file=") => {
+                pathspec_file = Some(PathBuf::from(&arg["--pathspec-from-file=".len()..]));
+            }
+            "--pathspec-file-nul" => pathspec_file_nul = true,
+            "--ignore-errors" => ignore_errors = Some(true),
+            "--no-ignore-errors" => ignore_errors = Some(false),
+            "-q" | "--quiet" => quiet = true,
+            "--" => {
+                // Everything after "--" is a path, even if it starts with '-'
+                file_paths.extend(args[i + 1..].iter().map(|arg| arg.as_bytes().to_vec()));
+                break;
+            }
+            arg if arg.starts_with('-') && arg != "-" => {
+                eprintln!("Unknown option: {}", arg);
+                std::process::exit(129);
+            }
+            _ => file_paths.push(args[i].as_bytes().to_vec()),
+        }
+        i += 1;
+    }
+    
+    if pathspec_file_nul && pathspec_file.is_none() {
+        eprintln!("fatal: the option '--pathspec-file-nul' requires '--pathspec-from-file'");
+        std::process::exit(128);
+    }
+    
+    // Read paths from the pathspec file instead of the command line
+    if let Some(file) = &pathspec_file {
+        if !file_paths.is_empty() {
+            eprintln!("fatal: '--pathspec-from-file' and pathspec arguments cannot be used together");
+            std::process::exit(128);
+        }
+        
+        file_paths = match pathspec::read_pathspec_file(file, pathspec_file_nul) {
+            Ok(paths) => paths,
+            Err(e) => {
+                eprintln!("fatal: {}", e);
+                std::process::exit(128);
+            }
+        };
+    }
+    
     // Check if user provided any files to add
-    if args.len() < 2 {
+    if file_paths.is_empty() {
         eprintln!("Nothing specified, nothing added.");
         eprintln!("hint: Maybe you wanted to say 'rakke add .'?");
         std::process::exit(1);
     }
     
-    // Extract file paths from command line (skip "add" command itself)
-    let file_paths: Vec<String> = args[1..].to_vec();
-    
     // Verify we are inside a git repository
-    if !Path::new(".git").exists() {
-        eprintln!("fatal: not a git repository (or any of the parent directories): .git");
-        std::process::exit(1);
-    }
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(1);
+        }
+    };
     
-    // Process each file or directory argument
-    for path in file_paths {
-        if let Err(e) = add_path(&path) {
+    // Add all file and directory arguments in one pass over the index
+    match add_paths(&repo, &file_paths, ignore_errors) {
+        Ok(summary) => {
+            // Only a batch run with --ignore-errors reports what happened
+            if summary.ignore_errors && !quiet {
+                eprintln!("added {} files, {} errors", summary.added, summary.errors);
+            }
+            if summary.errors > 0 {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
             eprintln!("fatal: {}", e);
             std::process::exit(1);
         }
     }
 }
 
-fn add_path(path: &str) -> Result<(), String> {
-    let path_obj = Path::new(path);
-    
-    // Check if the specified path exists
-    if !path_obj.exists() {
-        return Err(format!("pathspec '{}' did not match any files", path));
+// Outcome of an add run; errors are only counted when they don't abort it
+struct AddSummary {
+    added: usize,
+    errors: usize,
+    ignore_errors: bool,
+}
+
+fn add_paths(repo: &Repository, paths: &[Vec<u8>], ignore_errors: Option<bool>) -> Result<AddSummary, String> {
+    // Absolute pathspecs are matched against the worktree's real location
+    let root = fs::canonicalize(repo.work_tree()?)
+        .map_err(|e| format!("Cannot resolve worktree: {}", e))?;
+    
+    // On case-insensitive filesystems a path differing only in case is the same file
+    let config = Config::load(repo)?;
+    let ignore_case = config.get_bool("core.ignorecase").unwrap_or(false);
+    
+    // With --ignore-errors (or add.ignoreErrors) unreadable files and directories are
+    // skipped instead of aborting
+    let ignore_errors = ignore_errors
+        .or_else(|| config.get_bool("add.ignoreerrors"))
+        .unwrap_or(false);
+    let mut summary = AddSummary { added: 0, errors: 0, ignore_errors };
+    
+    // Expand every pathspec first so overlapping ones like "." and "foo" add each file once
+    let mut files: Vec<Vec<u8>> = Vec::new();
+    let mut seen: HashSet<Vec<u8>> = HashSet::new();
+    
+    for path in paths {
+        for file in resolve_path(&root, path, &mut summary)? {
+            if seen.insert(file.clone()) {
+                files.push(file);
+            }
+        }
     }
     
-    // Load existing index from .git/index file
-    let mut index = load_index()?;
+    // Load existing index, honouring GIT_INDEX_FILE
+    let index_file = index::index_path(repo, env::var_os("GIT_INDEX_FILE"));
+    let mut index = Index::load(&index_file)?;
     
-    if path_obj.is_file() {
-        // Add single file to the index
-        add_file_to_index(&mut index, path)?;
-    } else if path_obj.is_dir() {
-        // Add entire directory recursively to the index
-        add_directory_to_index(&mut index, path)?;
+    // Folded once up front, so each file is a lookup instead of a scan of the whole index
+    let mut folded = if ignore_case { case_folded_paths(&index) } else { HashMap::new() };
+    
+    for file in &files {
+        let index_path = if ignore_case && index.get(file).is_none() {
+            folded.get(&paths::fold_case(file)).cloned().unwrap_or_else(|| file.clone())
+        } else {
+            file.clone()
+        };
+        
+        match add_file_to_index(repo, &mut index, file, index_path.clone()) {
+            Ok(()) => {
+                summary.added += 1;
+                if ignore_case {
+                    folded.entry(paths::fold_case(&index_path)).or_insert(index_path);
+                }
+            }
+            Err(e) if ignore_errors => {
+                eprintln!("error: {}", e);
+                eprintln!("error: unable to index file '{}'", String::from_utf8_lossy(file));
+                summary.errors += 1;
+            }
+            Err(e) => return Err(e),
+        }
     }
     
-    // Save the updated index back to .git/index file
-    save_index(&index)?;
+    // Save the updated index back to the same file, including everything added despite errors
+    index.write(repo, &index_file)?;
     
-    Ok(())
+    Ok(summary)
 }
 
-fn add_file_to_index(index: &mut HashMap<String, IndexEntry>, file_path: &str) -> Result<(), String> {
-    // Read the entire file content into memory
-    let content = fs::read(file_path)
-        .map_err(|e| format!("Cannot read file '{}': {}", file_path, e))?;
-    
-    // Create git blob object and get its SHA-1 hash
-    let blob_hash = create_blob_object(&content)?;
-    
-    // Get file system metadata (size, permissions, modification time)
-    let metadata = fs::metadata(file_path)
-        .map_err(|e| format!("Cannot get metadata for '{}': {}", file_path, e))?;
-    
-    // Create index entry with file information
-    let entry = IndexEntry {
-        hash: blob_hash,
-        mode: get_file_mode(&metadata),
-        size: content.len() as u32,
-        mtime: get_mtime(&metadata),
+// Expand a single path argument into normalized file paths relative to the repository root.
+// Paths are handled as raw bytes, so names that aren't valid UTF-8 are added unchanged.
+fn resolve_path(root: &Path, path: &[u8], summary: &mut AddSummary) -> Result<Vec<Vec<u8>>, String> {
+    let path_obj = paths::from_bytes(path)?;
+    
+    // Check if the specified path exists; a symlink counts even when its target doesn't
+    let file_type = match fs::symlink_metadata(&path_obj) {
+        Ok(metadata) => metadata.file_type(),
+        Err(_) => return Err(format!("pathspec '{}' did not match any files", String::from_utf8_lossy(path))),
     };
     
-    // Insert or update the file in the index
-    index.insert(file_path.to_string(), entry);
+    let mut candidates = Vec::new();
     
-    Ok(())
-}
-
-fn add_directory_to_index(index: &mut HashMap<String, IndexEntry>, dir_path: &str) -> Result<(), String> {
-    // Collect all files in directory recursively
-    let mut files_to_add = Vec::new();
-    collect_files(Path::new(dir_path), &mut files_to_add)?;
+    if file_type.is_file() || file_type.is_symlink() {
+        // Single file argument; a symlink is added as a link, never followed
+        candidates.push(path_obj);
+    } else if file_type.is_dir() {
+        // Collect all files in directory recursively
+        collect_files(&path_obj, &mut candidates, summary)?;
+    }
     
-    // Add each collected file to the index
-    for file_path in files_to_add {
+    let mut files = Vec::new();
+    for candidate in candidates {
+        let normalized = repo_relative(root, &candidate)?
+            .ok_or_else(|| format!("'{}' is outside repository at '{}'", candidate.display(), root.display()))?;
+        
         // Skip .git directory and its contents
-        if file_path.starts_with(".git/") || file_path == ".git" {
+        if normalized == b".git" || normalized.starts_with(b".git/") {
             continue;
         }
         
-        add_file_to_index(index, &file_path)?;
+        files.push(normalized);
     }
     
-    Ok(())
+    Ok(files)
 }
 
-fn collect_files(dir: &Path, files: &mut Vec<String>) -> Result<(), String> {
-    // Read directory entries
-    let entries = fs::read_dir(dir)
-        .map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+// The path of a file relative to the repository root, which must be absolute, or None if
+// the file lies outside it. Relative paths are taken from the root, where add runs.
+fn repo_relative(root: &Path, path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let root_bytes = paths::to_bytes(root)?;
+    let absolute = paths::to_bytes(&root.join(path))?;
+    if let Some(relative) = strip_root(&root_bytes, &absolute) {
+        return Ok(Some(relative));
+    }
     
-    // Process each entry in the directory
-    for entry in entries {
-        let entry = entry
-            .map_err(|e| format!("Cannot read directory entry: {}", e))?;
-        
-        let path = entry.path();
-        let path_str = path.to_str()
-            .ok_or_else(|| "Invalid UTF-8 in file path".to_string())?;
-        
-        if path.is_file() {
-            // Add regular file to the list
-            files.push(path_str.to_string());
-        } else if path.is_dir() {
-            // Recursively process subdirectory
-            collect_files(&path, files)?;
+    // An absolute path may reach the worktree through a symlink, such as /tmp on macOS;
+    // resolve the directory but not the file itself, which may be a symlink to add as such
+    if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+        if path.is_absolute() {
+            if let Ok(parent) = fs::canonicalize(parent) {
+                return Ok(strip_root(&root_bytes, &paths::to_bytes(&parent.join(name))?));
+            }
         }
     }
     
-    Ok(())
+    Ok(None)
 }
 
-fn create_blob_object(content: &[u8]) -> Result<String, String> {
-    // Create git blob object format: "blob <size>\0<content>"
-    let header = format!("blob {}\0", content.len());
-    let mut object_content = header.into_bytes();
-    object_content.extend_from_slice(content);
-    
-    // Calculate SHA-1 hash of the complete object
-    let hash = sha1_hash(&object_content);
-    
-    // Compress object content using zlib
-    let compressed = compress_zlib(&object_content)?;
-    
-    // Create object file path: .git/objects/xx/yyyyyyy...
-    let (dir_name, file_name) = hash.split_at(2);
-    let object_dir = format!(".git/objects/{}", dir_name);
-    let object_path = format!("{}/{}", object_dir, file_name);
-    
-    // Create object directory if it doesn't exist
-    if !Path::new(&object_dir).exists() {
-        fs::create_dir_all(&object_dir)
-            .map_err(|e| format!("Cannot create object directory: {}", e))?;
-    }
+// Normalize an absolute path and remove the root from its start
+fn strip_root(root: &[u8], path: &[u8]) -> Option<Vec<u8>> {
+    let root = normalize_path(root)?;
+    let path = normalize_path(path)?;
     
-    // Write compressed object to file (only if it doesn't already exist)
-    if !Path::new(&object_path).exists() {
-        fs::write(&object_path, compressed)
-            .map_err(|e| format!("Cannot write object file: {}", e))?;
+    if root.is_empty() {
+        return Some(path);
     }
-    
-    Ok(hash)
-}
-
-fn load_index() -> Result<HashMap<String, IndexEntry>, String> {
-    let index_path = ".git/index";
-    
-    // Return empty index if file doesn't exist yet
-    if !Path::new(index_path).exists() {
-        return Ok(HashMap::new());
+    match path.strip_prefix(root.as_slice()) {
+        Some(b"") => Some(Vec::new()),
+        Some(rest) => rest.strip_prefix(b"/").map(|rest| rest.to_vec()),
+        None => None,
     }
-    
-    // Read existing index file
-    let content = fs::read(index_path)
-        .map_err(|e| format!("Cannot read index file: {}", e))?;
-    
-    // Parse index file format
-    parse_index(&content)
 }
 
-fn save_index(index: &HashMap<String, IndexEntry>) -> Result<(), String> {
-    let index_path = ".git/index";
-    
-    // Serialize index to git index format
-    let content = serialize_index(index)?;
-    
-    // Write serialized index to file
-    fs::write(index_path, content)
-        .map_err(|e| format!("Cannot write index file: {}", e))?;
+// Normalize a path like "./src//a/../b.rs" to "src/b.rs"; None if it climbs above its start.
+// A leading '/' is dropped, so absolute paths must be compared with absolute paths.
+fn normalize_path(path: &[u8]) -> Option<Vec<u8>> {
+    let mut components: Vec<&[u8]> = Vec::new();
+    
+    for component in path.split(|&b| b == b'/') {
+        match component {
+            b"" | b"." => {}
+            b".." => {
+                components.pop()?;
+            }
+            _ => components.push(component),
+        }
+    }
     
-    Ok(())
-}
-
-fn parse_index(_content: &[u8]) -> Result<HashMap<String, IndexEntry>, String> {
-    // TODO: Implement proper git index file parsing
-    // For now, return empty index (existing files will be re-added)
-    Ok(HashMap::new())
+    Some(components.join(&b'/'))
 }
 
-fn serialize_index(index: &HashMap<String, IndexEntry>) -> Result<Vec<u8>, String> {
-    let mut content = Vec::new();
-    
-    // Write git index file signature "DIRC" (DIRtory Cache)
-    content.extend_from_slice(b"DIRC");
-    
-    // Write index format version (version 2)
-    content.write_u32::<BigEndian>(2)
-        .map_err(|e| format!("Cannot write version: {}", e))?;
-    
-    // Write total number of index entries
-    content.write_u32::<BigEndian>(index.len() as u32)
-        .map_err(|e| format!("Cannot write entry count: {}", e))?;
-    
-    // Sort index entries by path for consistent output
-    let mut entries: Vec<_> = index.iter().collect();
-    entries.sort_by_key(|(path, _)| *path);
-    
-    // Write each index entry
-    for (path, entry) in entries {
-        write_index_entry(&mut content, path, entry)?;
+// The spelling the index already uses for each path, keyed by its case-folded form
+fn case_folded_paths(index: &Index) -> HashMap<Vec<u8>, Vec<u8>> {
+    let mut folded = HashMap::new();
+    for entry in index.entries() {
+        folded.entry(paths::fold_case(&entry.path)).or_insert_with(|| entry.path.clone());
     }
-    
-    // Calculate and append SHA-1 checksum of entire index
-    let checksum = sha1_hash(&content);
-    let checksum_bytes = hex_to_bytes(&checksum)?;
-    content.extend_from_slice(&checksum_bytes);
-    
-    Ok(content)
+    folded
 }
 
-fn write_index_entry(content: &mut Vec<u8>, path: &str, entry: &IndexEntry) -> Result<(), String> {
-    // Write creation time (set to modification time for simplicity)
-    content.write_u32::<BigEndian>(entry.mtime)
-        .map_err(|e| format!("Cannot write ctime: {}", e))?;
-    content.write_u32::<BigEndian>(0) // nanoseconds
-        .map_err(|e| format!("Cannot write ctime_ns: {}", e))?;
-    
-    // Write modification time
-    content.write_u32::<BigEndian>(entry.mtime)
-        .map_err(|e| format!("Cannot write mtime: {}", e))?;
-    content.write_u32::<BigEndian>(0) // nanoseconds
-        .map_err(|e| format!("Cannot write mtime_ns: {}", e))?;
-    
-    // Write device and inode (set to 0 for cross-platform compatibility)
-    content.write_u32::<BigEndian>(0) // device
-        .map_err(|e| format!("Cannot write device: {}", e))?;
-    content.write_u32::<BigEndian>(0) // inode
-        .map_err(|e| format!("Cannot write inode: {}", e))?;
-    
-    // Write file mode (permissions and file type)
-    content.write_u32::<BigEndian>(entry.mode)
-        .map_err(|e| format!("Cannot write mode: {}", e))?;
-    
-    // Write user and group IDs (set to 0 for simplicity)
-    content.write_u32::<BigEndian>(0) // uid
-        .map_err(|e| format!("Cannot write uid: {}", e))?;
-    content.write_u32::<BigEndian>(0) // gid
-        .map_err(|e| format!("Cannot write gid: {}", e))?;
-    
-    // Write file size
-    content.write_u32::<BigEndian>(entry.size)
-        .map_err(|e| format!("Cannot write size: {}", e))?;
-    
-    // Write SHA-1 hash (20 bytes)
-    let hash_bytes = hex_to_bytes(&entry.hash)?;
-    if hash_bytes.len() != 20 {
-        return Err("Invalid SHA-1 hash length".to_string());
-    }
-    content.extend_from_slice(&hash_bytes);
-    
-    // Write flags (assume no conflicts, stage 0)
-    let path_len = std::cmp::min(path.len(), 0xfff); // max 12 bits for path length
-    content.write_u16::<BigEndian>(path_len as u16)
-        .map_err(|e| format!("Cannot write flags: {}", e))?;
+// Add a worktree file, storing it under index_path (which may differ in case from file_path)
+fn add_file_to_index(repo: &Repository, index: &mut Index, file_path: &[u8], index_path: Vec<u8>) -> Result<(), String> {
+    let file_path = paths::from_bytes(file_path)?;
+    
+    // Get file system metadata (size, permissions, modification time) of the file itself
+    let metadata = fs::symlink_metadata(&file_path)
+        .map_err(|e| format!("Cannot get metadata for '{}': {}", file_path.display(), e))?;
+    
+    // Store the file as a git blob object and get its SHA-1 hash; a symlink's blob is its target
+    let (blob_hash, mode, size) = if metadata.file_type().is_symlink() {
+        let target = fs::read_link(&file_path)
+            .map_err(|e| format!("Cannot read symlink '{}': {}", file_path.display(), e))?;
+        let target = paths::to_bytes(&target)?;
+        (objects::write_object(repo, "blob", &target)?, 0o120000, target.len() as u32)
+    } else {
+        let blob_hash = objects::hash_file(&file_path, "blob", Some(repo))?;
+        (blob_hash, get_file_mode(&metadata), metadata.len() as u32)
+    };
     
-    // Write file path with null terminator
-    content.extend_from_slice(path.as_bytes());
-    content.push(0); // null terminator
+    // Create index entry with file information
+    let entry = Entry {
+        path: index_path,
+        hash: blob_hash,
+        mode,
+        size,
+        mtime: get_mtime(&metadata),
+        stage: 0,
+    };
     
-    // Pad to 8-byte boundary for proper alignment
-    while content.len() % 8 != 0 {
-        content.push(0);
-    }
+    // Insert or update the file in the index
+    index.add(entry);
     
     Ok(())
 }
 
-// Index entry structure representing a single file in the git index
-#[derive(Debug, Clone)]
-struct IndexEntry {
-    hash: String,    // SHA-1 hash of the file content
-    mode: u32,       // File permissions and type
-    size: u32,       // File size in bytes
-    mtime: u32,      // Last modification time
-}
-
-// Calculate SHA-1 hash using a simple implementation
-fn sha1_hash(data: &[u8]) -> String {
-    // Simple SHA-1 implementation for git objects
-    // NOTE: This is a basic implementation, production code should use a crypto library
-    
-    let mut h0: u32 = 0x67452301;
-    let mut h1: u32 = 0xEFCDAB89;
-    let mut h2: u32 = 0x98BADCFE;
-    let mut h3: u32 = 0x10325476;
-    let mut h4: u32 = 0xC3D2E1F0;
-    
-    // Pre-processing: adding padding bits
-    let mut padded = data.to_vec();
-    let original_len = data.len();
-    
-    // Append '1' bit (0x80 byte)
-    padded.push(0x80);
-    
-    // Append zeros until length ≡ 448 (mod 512)
-    while (padded.len() % 64) != 56 {
-        padded.push(0);
-    }
-    
-    // Append original length as 64-bit big-endian
-    let bit_len = (original_len as u64) * 8;
-    padded.extend_from_slice(&bit_len.to_be_bytes());
-    
-    // Process message in 512-bit chunks
-    for chunk in padded.chunks_exact(64) {
-        let mut w = [0u32; 80];
-        
-        // Break chunk into sixteen 32-bit words
-        for i in 0..16 {
-            w[i] = u32::from_be_bytes([
-                chunk[i * 4],
-                chunk[i * 4 + 1], 
-                chunk[i * 4 + 2],
-                chunk[i * 4 + 3]
-            ]);
-        }
-        
-        // Extend words
-        for i in 16..80 {
-            w[i] = (w[i-3] ^ w[i-8] ^ w[i-14] ^ w[i-16]).rotate_left(1);
+// Find the files under dir; a directory that can't be read is counted as an error under --ignore-errors
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, summary: &mut AddSummary) -> Result<(), String> {
+    // Read directory entries
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let e = format!("Cannot read directory '{}': {}", dir.display(), e);
+            if !summary.ignore_errors {
+                return Err(e);
+            }
+            eprintln!("error: {}", e);
+            summary.errors += 1;
+            return Ok(());
         }
+    };
+    
+    // Process each entry in the directory
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| format!("Cannot read directory entry: {}", e))?;
         
-        // Initialize hash values for this chunk
-        let (mut a, mut b, mut c, mut d, mut e) = (h0, h1, h2, h3, h4);
+        let path = entry.path();
+        let file_type = entry.file_type()
+            .map_err(|e| format!("Cannot read file type of '{}': {}", path.display(), e))?;
         
-        // Main loop
-        for i in 0..80 {
-            let (f, k) = match i {
-                0..=19 => ((b & c) | ((!b) & d), 0x5A827999),
-                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
-                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
-                60..=79 => (b ^ c ^ d, 0xCA62C1D6),
-                _ => unreachable!(),
-            };
-            
-            let temp = a.rotate_left(5)
-                .wrapping_add(f)
-                .wrapping_add(e)
-                .wrapping_add(k)
-                .wrapping_add(w[i]);
-            
-            e = d;
-            d = c;
-            c = b.rotate_left(30);
-            b = a;
-            a = temp;
+        if file_type.is_file() || file_type.is_symlink() {
+            // Add regular files and symlinks, without following the latter, to the list
+            files.push(path);
+        } else if file_type.is_dir() {
+            // Recursively process subdirectory
+            collect_files(&path, files, summary)?;
         }
-        
-        // Add this chunk's hash to result
-        h0 = h0.wrapping_add(a);
-        h1 = h1.wrapping_add(b);
-        h2 = h2.wrapping_add(c);
-        h3 = h3.wrapping_add(d);
-        h4 = h4.wrapping_add(e);
     }
     
-    // Format final hash as hexadecimal string
-    format!("{:08x}{:08x}{:08x}{:08x}{:08x}", h0, h1, h2, h3, h4)
-}
-
-// Compress data using zlib compression
-fn compress_zlib(data: &[u8]) -> Result<Vec<u8>, String> {
-    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(data)
-        .map_err(|e| format!("Compression error: {}", e))?;
-    
-    encoder.finish()
-        .map_err(|e| format!("Compression finish error: {}", e))
+    Ok(())
 }
 
 // Get file mode (permissions) from metadata
@@ -413,20 +382,4 @@ fn get_mtime(metadata: &fs::Metadata) -> u32 {
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs() as u32
-}
-
-// Convert hexadecimal string to byte array
-fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
-    let mut bytes = Vec::new();
-    
-    // Process hex string in pairs of characters
-    for chunk in hex.as_bytes().chunks(2) {
-        let hex_str = std::str::from_utf8(chunk)
-            .map_err(|_| "Invalid hex string")?;
-        let byte = u8::from_str_radix(hex_str, 16)
-            .map_err(|_| "Invalid hex digit")?;
-        bytes.push(byte);
-    }
-    
-    Ok(bytes)
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+use rakke::config::Config;
+use rakke::objects;
+use rakke::paths;
+use rakke::refs;
+use rakke::repository::Repository;
+
+// What to show about the object
+#[derive(PartialEq)]
+enum Mode {
+    Type,
+    Size,
+    Pretty,
+    Exists,
+}
+
+pub fn execute(args: Vec<String>) {
+    let mut mode: Option<Mode> = None;
+    let mut name: Option<String> = None;
+    
+    // Process arguments (skip "cat-file" command itself)
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "-t" => mode = Some(Mode::Type),
+            "-s" => mode = Some(Mode::Size),
+            "-p" => mode = Some(Mode::Pretty),
+            "-e" => mode = Some(Mode::Exists),
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg if !arg.starts_with('-') && name.is_none() => name = Some(arg.to_string()),
+            _ => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+    }
+    
+    let (mode, name) = match (mode, name) {
+        (Some(mode), Some(name)) => (mode, name),
+        _ => {
+            print_help();
+            std::process::exit(129);
+        }
+    };
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    let hash = match resolve_name(&repo, &name) {
+        Ok(hash) => hash,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    // Only the header is needed to tell the type and size, so these work for objects of any size;
+    // -e only reports through the exit status whether a well-formed name refers to an object
+    let header = objects::read_object_header(&repo, &hash);
+    if mode == Mode::Exists {
+        std::process::exit(if header.is_ok() { 0 } else { 1 });
+    }
+    
+    let (kind, size) = match header {
+        Ok(header) => header,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    let output = match mode {
+        Mode::Type => format!("{}\n", kind).into_bytes(),
+        Mode::Size => format!("{}\n", size).into_bytes(),
+        _ if kind == "tree" => {
+            let listing = objects::read_object(&repo, &hash)
+                .and_then(|(_, content)| Config::load(&repo).and_then(|config| format_tree(&content, &config)));
+            match listing {
+                Ok(listing) => listing,
+                Err(e) => {
+                    eprintln!("fatal: {}", e);
+                    std::process::exit(128);
+                }
+            }
+        }
+        // Blobs, commits and tags are shown as stored, streamed so that even objects over
+        // rakke.maxObjectSize are never held in memory whole
+        _ => match objects::stream_object(&repo, &hash, &mut io::stdout().lock()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                eprintln!("fatal: {}", e);
+                std::process::exit(128);
+            }
+            // Already written while inflating; a closed pipe is not worth an error message either
+            _ => return,
+        },
+    };
+    
+    // A closed pipe (e.g. "| head") is not worth an error message
+    let _ = io::stdout().write_all(&output);
+}
+
+// Turn a ref name, full object id or abbreviated object id into an object id
+fn resolve_name(repo: &Repository, name: &str) -> Result<String, String> {
+    if objects::is_valid_hash(name) {
+        return Ok(name.to_string());
+    }
+    
+    // Ref names are tried in the same order as git's rev-parse
+    for candidate in [name.to_string(), format!("refs/{}", name), format!("refs/tags/{}", name), format!("refs/heads/{}", name)] {
+        if candidate == "HEAD" || candidate.starts_with("refs/") {
+            if let Some(hash) = refs::read_ref(repo, &candidate)? {
+                return Ok(hash);
+            }
+        }
+    }
+    
+    objects::find_object(repo, name)
+}
+
+// List a tree like git: "<mode> <type> <hash>\t<name>", with the mode padded to six digits
+// and unusual names quoted according to core.quotePath
+fn format_tree(content: &[u8], config: &Config) -> Result<Vec<u8>, String> {
+    let quote_path = config.get_bool("core.quotepath").unwrap_or(true);
+    let mut listing = Vec::new();
+    
+    for entry in objects::parse_tree(content)? {
+        listing.extend_from_slice(format!("{:06o} {} {}\t", entry.mode, objects::mode_object_type(entry.mode), entry.hash).as_bytes());
+        listing.extend_from_slice(&paths::quote_c_style(&entry.name, quote_path));
+        listing.push(b'\n');
+    }
+    
+    Ok(listing)
+}
+
+fn print_help() {
+    println!("usage: rakke cat-file (-t | -s | -e | -p) <object>");
+    println!();
+    println!("    -t                    show object type");
+    println!("    -s                    show object size");
+    println!("    -e                    exit with zero when there's no error");
+    println!("    -p                    pretty-print object's content");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
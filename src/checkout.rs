@@ -0,0 +1,81 @@
+use rakke::refs;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<String>) {
+    let mut orphan: Option<String> = None;
+    
+    // Process arguments (skip "checkout" command itself)
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--orphan" => {
+                // Branch name is given in the next argument
+                i += 1;
+                match args.get(i) {
+                    Some(name) => orphan = Some(name.clone()),
+                    None => {
+                        eprintln!("error: option 'orphan' requires a value");
+                        std::process::exit(129);
+                    }
+                }
+            }
+            arg if arg.starts_with("--orphan=") => orphan = Some(arg["--orphan=".len()..].to_string()),
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg => {
+                // Switching to existing commits needs a worktree update, which rakke can't do yet
+                eprintln!("fatal: unsupported checkout argument '{}'; only --orphan <new-branch> is implemented", arg);
+                std::process::exit(128);
+            }
+        }
+        i += 1;
+    }
+    
+    let branch = match orphan {
+        Some(branch) => branch,
+        None => {
+            print_help();
+            std::process::exit(129);
+        }
+    };
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    if let Err(e) = checkout_orphan(&repo, &branch) {
+        eprintln!("fatal: {}", e);
+        std::process::exit(128);
+    }
+    // Git reports the switch on stderr
+    eprintln!("Switched to a new branch '{}'", branch);
+}
+
+// Point HEAD at a new, unborn branch, keeping the index and worktree so the next
+// commit starts a separate history with everything currently staged
+fn checkout_orphan(repo: &Repository, branch: &str) -> Result<(), String> {
+    if !refs::is_valid_branch_name(branch) {
+        return Err(format!("'{}' is not a valid branch name", branch));
+    }
+    
+    let target = format!("refs/heads/{}", branch);
+    if refs::read_ref(repo, &target)?.is_some() {
+        return Err(format!("a branch named '{}' already exists", branch));
+    }
+    
+    refs::set_head_ref(repo, &target)
+}
+
+fn print_help() {
+    println!("usage: rakke checkout --orphan <new-branch>");
+    println!();
+    println!("    --orphan <new-branch> new unparented branch");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
@@ -0,0 +1,230 @@
+use std::env;
+use std::fs;
+use std::io;
+use rakke::config::Config;
+use rakke::ident::{self, Role};
+use rakke::index::{self, Index};
+use rakke::message::{self, Cleanup};
+use rakke::objects;
+use rakke::refs;
+use rakke::repository::Repository;
+use rakke::trailers::{self, IfExists, IfMissing, PolicyOverrides, Trailer, Where};
+use crate::commit_tree::{append_paragraph, read_message_file};
+
+pub fn execute(args: Vec<String>) {
+    let mut message: Option<String> = None;
+    let mut cleanup: Option<String> = None;
+    let mut allow_empty = false;
+    let mut signoff = false;
+    let mut quiet = false;
+    
+    // Process arguments (skip "commit" command itself)
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-m" | "--message" | "-F" | "--file" | "--cleanup" => {
+                // Options taking a value in the next argument
+                let option = args[i].clone();
+                i += 1;
+                let value = match args.get(i) {
+                    Some(value) => value.clone(),
+                    None => {
+                        eprintln!("error: option '{}' requires a value", option.trim_start_matches('-'));
+                        std::process::exit(129);
+                    }
+                };
+                apply_option(&option, &value, &mut message, &mut cleanup);
+            }
+            "--allow-empty" => allow_empty = true,
+            "-s" | "--signoff" => signoff = true,
+            "--no-signoff" => signoff = false,
+            "-q" | "--quiet" => quiet = true,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg if arg.starts_with("--") && arg.contains('=') => {
+                let (option, value) = arg.split_once('=').unwrap();
+                apply_option(option, value, &mut message, &mut cleanup);
+            }
+            arg => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+        i += 1;
+    }
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    // There is no editor support, so the message must be given up front
+    let message = match message {
+        Some(message) => message,
+        None => {
+            eprintln!("fatal: no commit message given; use -m or -F");
+            std::process::exit(128);
+        }
+    };
+    
+    match commit(&repo, &message, cleanup.as_deref(), allow_empty, signoff) {
+        Ok(summary) => {
+            if !quiet {
+                println!("{}", summary);
+            }
+        }
+        Err(CommitError::NothingToCommit) => {
+            println!("nothing to commit (use \"rakke add\" to stage changes, or --allow-empty)");
+            std::process::exit(1);
+        }
+        Err(CommitError::EmptyMessage) => {
+            eprintln!("Aborting commit due to empty commit message.");
+            std::process::exit(1);
+        }
+        Err(CommitError::Fatal(e)) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+fn apply_option(option: &str, value: &str, message: &mut Option<String>, cleanup: &mut Option<String>) {
+    match option {
+        "-m" | "--message" => append_paragraph(message, value),
+        "-F" | "--file" => match read_message_file(value) {
+            Ok(text) => append_paragraph(message, &text),
+            Err(e) => {
+                eprintln!("fatal: {}", e);
+                std::process::exit(128);
+            }
+        },
+        "--cleanup" => *cleanup = Some(value.to_string()),
+        _ => {
+            eprintln!("Unknown option: {}", option);
+            print_help();
+            std::process::exit(129);
+        }
+    }
+}
+
+// Why a commit was not made; only Fatal is an error in git's sense
+enum CommitError {
+    NothingToCommit,
+    EmptyMessage,
+    Fatal(String),
+}
+
+impl From<String> for CommitError {
+    fn from(e: String) -> Self {
+        CommitError::Fatal(e)
+    }
+}
+
+// Commit the staged entries on top of HEAD and move the current branch, returning the summary line
+fn commit(repo: &Repository, message: &str, cleanup: Option<&str>, allow_empty: bool, signoff: bool) -> Result<String, CommitError> {
+    let config = Config::load(repo)?;
+    
+    // Messages given with -m or -F are cleaned up as if no editor was involved
+    let cleanup_mode = cleanup.or(config.get("commit.cleanup")).unwrap_or("default");
+    let cleanup_mode = message::parse_cleanup(cleanup_mode, false)
+        .ok_or_else(|| format!("Invalid cleanup mode {}", cleanup_mode))?;
+    let message = message::clean_commit_message(message, cleanup_mode, message::comment_char(&config)?);
+    if cleanup_mode != Cleanup::Verbatim && message.is_empty() {
+        return Err(CommitError::EmptyMessage);
+    }
+    
+    // The sign-off names the committer, and isn't repeated if it already ends the trailers
+    let message = if signoff {
+        let committer = ident::resolve(Role::Committer, &config)?;
+        let sign_off = Trailer { key: "Signed-off-by".to_string(), value: format!("{} <{}>", committer.name, committer.email) };
+        let policy = PolicyOverrides { place: Some(Where::End), if_exists: Some(IfExists::AddIfDifferentNeighbor), if_missing: Some(IfMissing::Add) };
+        trailers::add_trailers(&message, &[(sign_off, policy)], &config)
+    } else {
+        message
+    };
+    
+    // HEAD either names a branch, possibly not created yet, or holds a commit directly
+    let branch = refs::head_ref(repo)?;
+    let head_name = branch.clone().unwrap_or_else(|| "HEAD".to_string());
+    let parent = refs::read_ref(repo, &head_name)?;
+    
+    // Nothing staged means an empty index on an unborn branch, or the parent's tree otherwise
+    let index = Index::load(index::index_path(repo, env::var_os("GIT_INDEX_FILE")))?;
+    if !allow_empty && parent.is_none() && index.entries().next().is_none() {
+        return Err(CommitError::NothingToCommit);
+    }
+    
+    // Conflicted paths still carry their stages; any other failure is reported as it is
+    if index.entries().any(|entry| entry.stage != 0) {
+        return Err(CommitError::Fatal("Committing is not possible because you have unmerged files.".to_string()));
+    }
+    let tree = index.write_tree(repo)?;
+    
+    // A merge being concluded records the merged commits as further parents, even with no change
+    let merge_heads = read_merge_heads(repo)?;
+    if let Some(parent) = &parent {
+        if !allow_empty && merge_heads.is_empty() && objects::commit_tree_hash(repo, parent)? == tree {
+            return Err(CommitError::NothingToCommit);
+        }
+    }
+    
+    let parents: Vec<String> = parent.iter().cloned().chain(merge_heads).collect();
+    let hash = objects::write_commit(repo, &tree, &parents, &message)?;
+    refs::update_ref(repo, &head_name, &hash, parent.as_deref())?;
+    
+    // The merge is complete once its commit exists
+    for name in ["MERGE_HEAD", "MERGE_MSG", "MERGE_MODE"] {
+        match fs::remove_file(repo.git_path(name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(format!("Cannot remove {}: {}", name, e).into()),
+            _ => {}
+        }
+    }
+    
+    // "[master (root-commit) 1a2b3c4] Subject", as git prints it
+    let branch_label = match &branch {
+        Some(branch) => branch.strip_prefix("refs/heads/").unwrap_or(branch).to_string(),
+        None => "detached HEAD".to_string(),
+    };
+    let root_label = if parent.is_none() { " (root-commit)" } else { "" };
+    let subject = message.lines().next().unwrap_or("");
+    Ok(format!("[{}{} {}] {}", branch_label, root_label, &hash[..7], subject))
+}
+
+// Commits named in MERGE_HEAD, one per line, left behind by a merge that stopped before committing
+fn read_merge_heads(repo: &Repository) -> Result<Vec<String>, String> {
+    let content = match fs::read_to_string(repo.git_path("MERGE_HEAD")) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("could not open '{}' for reading: {}", repo.git_path("MERGE_HEAD").display(), e)),
+    };
+    
+    content.lines()
+        .map(|line| {
+            if objects::is_valid_hash(line) {
+                Ok(line.to_string())
+            } else {
+                Err(format!("Corrupt MERGE_HEAD file ({})", line))
+            }
+        })
+        .collect()
+}
+
+fn print_help() {
+    println!("usage: rakke commit [-q] [-s] [--allow-empty] [--cleanup=<mode>] [(-m <message>)...] [(-F <file>)...]");
+    println!();
+    println!("    -m, --message <message>");
+    println!("                          commit message");
+    println!("    -F, --file <file>     read message from file");
+    println!("    --cleanup <mode>      how to strip spaces and #comments from message");
+    println!("    -s, --signoff         add a Signed-off-by trailer");
+    println!("    --allow-empty         ok to record an empty change");
+    println!("    -q, --quiet           suppress summary after successful commit");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
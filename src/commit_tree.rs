@@ -0,0 +1,146 @@
+use std::fs;
+use std::io::{self, Read};
+use rakke::objects;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<String>) {
+    let mut tree: Option<String> = None;
+    let mut parents: Vec<String> = Vec::new();
+    let mut message: Option<String> = None;
+    
+    // Process arguments (skip "commit-tree" command itself)
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-p" | "-m" | "-F" => {
+                // Options taking a value in the next argument
+                let option = args[i].clone();
+                i += 1;
+                let value = match args.get(i) {
+                    Some(value) => value.clone(),
+                    None => {
+                        eprintln!("error: switch '{}' requires a value", &option[1..]);
+                        std::process::exit(129);
+                    }
+                };
+                
+                match option.as_str() {
+                    "-p" => parents.push(value),
+                    "-m" => append_paragraph(&mut message, &value),
+                    _ => match read_message_file(&value) {
+                        Ok(text) => append_paragraph(&mut message, &text),
+                        Err(e) => {
+                            eprintln!("fatal: {}", e);
+                            std::process::exit(128);
+                        }
+                    },
+                }
+            }
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg if !arg.starts_with('-') && tree.is_none() => tree = Some(arg.to_string()),
+            _ => {
+                eprintln!("Unknown option: {}", args[i]);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+        i += 1;
+    }
+    
+    let tree = match tree {
+        Some(tree) => tree,
+        None => {
+            print_help();
+            std::process::exit(129);
+        }
+    };
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    // The tree and parents may be abbreviated ids
+    let resolve = |name: &str| {
+        objects::find_object(&repo, name).unwrap_or_else(|e| {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        })
+    };
+    let tree = resolve(&tree);
+    
+    // Git ignores repeated parents with a warning, however they were spelled
+    let mut parent_ids: Vec<String> = Vec::new();
+    for parent in &parents {
+        let hash = resolve(parent);
+        if parent_ids.contains(&hash) {
+            eprintln!("error: duplicate parent {} ignored", hash);
+        } else {
+            parent_ids.push(hash);
+        }
+    }
+    
+    // Without -m or -F the message is read from stdin
+    let message = match message {
+        Some(message) => message,
+        None => {
+            let mut text = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut text) {
+                eprintln!("fatal: Cannot read commit message from stdin: {}", e);
+                std::process::exit(128);
+            }
+            text
+        }
+    };
+    
+    match objects::write_commit(&repo, &tree, &parent_ids, &message) {
+        Ok(hash) => println!("{}", hash),
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+// Each -m or -F adds a paragraph, separated from the previous one by a blank line
+pub fn append_paragraph(message: &mut Option<String>, text: &str) {
+    let buffer = message.get_or_insert_with(String::new);
+    
+    if !buffer.is_empty() {
+        buffer.push('\n');
+    }
+    buffer.push_str(text);
+    
+    if !buffer.ends_with('\n') {
+        buffer.push('\n');
+    }
+}
+
+// Read a message file given to -F, where "-" means stdin
+pub fn read_message_file(file: &str) -> Result<String, String> {
+    if file == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)
+            .map_err(|e| format!("Cannot read commit message from stdin: {}", e))?;
+        return Ok(text);
+    }
+    
+    fs::read_to_string(file)
+        .map_err(|e| format!("could not read log file '{}': {}", file, e))
+}
+
+fn print_help() {
+    println!("usage: rakke commit-tree <tree> [(-p <parent>)...] [(-m <message>)...] [(-F <file>)...]");
+    println!();
+    println!("    -p <parent>           id of a parent commit object");
+    println!("    -m <message>          commit message");
+    println!("    -F <file>             read commit log message from file");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
@@ -0,0 +1,314 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::repository::Repository;
+
+// Parsed git configuration, merged from the global and repository files
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    entries: Vec<(String, String)>, // Normalized key and raw value, in file order
+}
+
+impl Config {
+    // Load global (~/.gitconfig) and repository (.git/config) settings
+    pub fn load(repo: &Repository) -> Result<Config, String> {
+        let mut config = Config::load_global()?;
+        
+        // Repository settings override the global ones
+        let path = repo.git_path("config");
+        if path.exists() {
+            config.read_file(&path)?;
+        }
+        
+        Ok(config)
+    }
+    
+    // Load only the global settings, for commands that may run outside a repository
+    pub fn load_global() -> Result<Config, String> {
+        let mut config = Config::default();
+        
+        // Later files override earlier ones, so read the most general first
+        for path in config_paths() {
+            if path.exists() {
+                config.read_file(&path)?;
+            }
+        }
+        
+        Ok(config)
+    }
+    
+    // Look up the last value set for a key such as "user.name" or "trailer.sign.key"
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = normalize_key(key)?;
+        
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+    
+    // All settings as (normalized key, value) pairs, in the order they were read
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+    
+    // Look up a key and interpret it as a git boolean
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(parse_bool)
+    }
+    
+    // Look up a key and interpret it as a size, which may carry a k, m or g suffix
+    pub fn get_size(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(parse_size)
+    }
+    
+    // List the distinct subsection names used under a section, e.g. the tokens of [trailer "x"]
+    pub fn subsections(&self, section: &str) -> Vec<String> {
+        let prefix = format!("{}.", section.to_lowercase());
+        let mut names: Vec<String> = Vec::new();
+        
+        for (key, _) in &self.entries {
+            if let Some(rest) = key.strip_prefix(&prefix) {
+                // Keys with a subsection have the form "<subsection>.<name>"
+                if let Some(pos) = rest.rfind('.') {
+                    let name = rest[..pos].to_string();
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        
+        names
+    }
+    
+    fn read_file(&mut self, path: &Path) -> Result<(), String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read config file '{}': {}", path.display(), e))?;
+        
+        self.parse(&content)
+            .map_err(|line| format!("bad config line {} in file {}", line, path.display()))
+    }
+    
+    // Parse config text, returning the offending line number on error
+    fn parse(&mut self, content: &str) -> Result<(), usize> {
+        // Editors on Windows may start the file with a UTF-8 byte order mark; CRLF endings
+        // are handled by lines(), and continuation lines are joined after it strips them
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut section = String::new();
+        let mut lines = content.lines().enumerate();
+        
+        while let Some((index, raw_line)) = lines.next() {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            
+            // Skip blank lines and comments
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            
+            if line.starts_with('[') {
+                section = parse_section_header(line).ok_or(line_number)?;
+                continue;
+            }
+            
+            // Variables are only valid inside a section
+            if section.is_empty() {
+                return Err(line_number);
+            }
+            
+            let (name, rest) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), Some(&line[pos + 1..])),
+                None => (line, None),
+            };
+            
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(line_number);
+            }
+            
+            let value = match rest {
+                Some(rest) => {
+                    // Join continuation lines ending with a backslash
+                    let mut raw_value = rest.to_string();
+                    while ends_with_continuation(&raw_value) {
+                        raw_value.pop();
+                        match lines.next() {
+                            Some((_, next)) => raw_value.push_str(next),
+                            None => break,
+                        }
+                    }
+                    parse_value(&raw_value).ok_or(line_number)?
+                }
+                // A bare key is shorthand for "true"
+                None => "true".to_string(),
+            };
+            
+            self.entries.push((format!("{}.{}", section, name.to_lowercase()), value));
+        }
+        
+        Ok(())
+    }
+}
+
+// Interpret a config value as a git boolean
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" | "" => Some(false),
+        _ => None,
+    }
+}
+
+// Interpret a config value as a size such as "512" or "64m", like git's integer values
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (digits, factor) = match value.chars().last()?.to_ascii_lowercase() {
+        'k' => (&value[..value.len() - 1], 1 << 10),
+        'm' => (&value[..value.len() - 1], 1 << 20),
+        'g' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    
+    digits.parse::<u64>().ok()?.checked_mul(factor)
+}
+
+// Configuration files in the order they are applied
+fn config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    
+    if let Ok(xdg) = env::var("XDG_CONFIG_HOME") {
+        paths.push(Path::new(&xdg).join("git/config"));
+    } else if let Ok(home) = env::var("HOME") {
+        paths.push(Path::new(&home).join(".config/git/config"));
+    }
+    
+    if let Ok(home) = env::var("HOME") {
+        paths.push(Path::new(&home).join(".gitconfig"));
+    }
+    
+    paths
+}
+
+// Normalize "Section.Sub.Name" so that section and name compare case-insensitively
+fn normalize_key(key: &str) -> Option<String> {
+    let first = key.find('.')?;
+    let last = key.rfind('.')?;
+    
+    let section = key[..first].to_lowercase();
+    let name = key[last + 1..].to_lowercase();
+    
+    if first == last {
+        Some(format!("{}.{}", section, name))
+    } else {
+        // Subsection names are case-sensitive
+        Some(format!("{}.{}.{}", section, &key[first + 1..last], name))
+    }
+}
+
+// Parse "[section]" or "[section "subsection"]" into a normalized key prefix
+fn parse_section_header(line: &str) -> Option<String> {
+    let inner = line.strip_prefix('[')?;
+    let end = inner.rfind(']')?;
+    let inner = inner[..end].trim();
+    
+    match inner.find('"') {
+        Some(quote) => {
+            let section = inner[..quote].trim();
+            let subsection = inner[quote + 1..].strip_suffix('"')?;
+            Some(format!("{}.{}", section.to_lowercase(), subsection.replace("\\\"", "\"")))
+        }
+        // Legacy "[section.subsection]" syntax lowercases the whole header
+        None => Some(inner.to_lowercase()),
+    }
+}
+
+fn ends_with_continuation(value: &str) -> bool {
+    let trailing = value.chars().rev().take_while(|c| *c == '\\').count();
+    trailing % 2 == 1
+}
+
+// Strip comments, surrounding whitespace and quotes from a raw value
+fn parse_value(raw: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut pending_space = String::new();
+    let mut chars = raw.trim_start().chars();
+    
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' | ';' if !in_quotes => break,
+            '\\' => {
+                value.push_str(&pending_space);
+                pending_space.clear();
+                match chars.next()? {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'b' => { value.pop(); }
+                    '"' => value.push('"'),
+                    '\\' => value.push('\\'),
+                    _ => return None,
+                }
+            }
+            // Whitespace outside quotes is kept only when followed by more content
+            c if c.is_whitespace() && !in_quotes => pending_space.push(c),
+            c => {
+                value.push_str(&pending_space);
+                pending_space.clear();
+                value.push(c);
+            }
+        }
+    }
+    
+    if in_quotes {
+        return None;
+    }
+    
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parse(content: &str) -> Result<Config, usize> {
+        let mut config = Config::default();
+        config.parse(content)?;
+        Ok(config)
+    }
+    
+    #[test]
+    fn bom_and_crlf_config_parses() {
+        // The same values git config -l reports for this file
+        let config = parse(
+            "\u{feff}[core]\r\n\tbare = false\r\n[user]\r\n\tname = A U Thor\r\n\temail = \"quoted\" # c\r\n\
+             [alias]\r\n\tlong = one \\\r\n two\r\n[Section \"Sub\"]\r\n\tKey\r\n",
+        )
+        .unwrap();
+        
+        assert_eq!(config.get_bool("core.bare"), Some(false));
+        assert_eq!(config.get("user.name"), Some("A U Thor"));
+        assert_eq!(config.get("user.email"), Some("quoted"));
+        assert_eq!(config.get("alias.long"), Some("one  two"));
+        assert_eq!(config.get_bool("section.Sub.key"), Some(true));
+    }
+    
+    #[test]
+    fn bom_and_crlf_config_file_is_read() {
+        let path = env::temp_dir().join(format!("rakke-config-bom-{}", std::process::id()));
+        fs::write(&path, b"\xef\xbb\xbf[core]\r\n\tcommentChar = %\r\n").unwrap();
+        
+        let mut config = Config::default();
+        let result = config.read_file(&path);
+        let _ = fs::remove_file(&path);
+        result.unwrap();
+        assert_eq!(config.get("core.commentchar"), Some("%"));
+    }
+    
+    #[test]
+    fn bom_only_counts_at_the_start() {
+        assert_eq!(parse("[core]\n\u{feff}bare = true\n").unwrap_err(), 2);
+        assert!(parse("\u{feff}").unwrap().entries().is_empty());
+    }
+}
\ No newline at end of file
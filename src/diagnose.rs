@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use rakke::config::Config;
+use rakke::fsck;
+use rakke::index::{self, Index};
+use rakke::objects;
+use rakke::paths;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<String>) {
+    let mut output_dir = ".".to_string();
+    let mut include_paths = false;
+    
+    // Process arguments (skip "diagnose" command itself)
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output-directory" => {
+                // Directory is given in the next argument
+                i += 1;
+                match args.get(i) {
+                    Some(dir) => output_dir = dir.clone(),
+                    None => {
+                        eprintln!("error: option 'output-directory' requires a value");
+                        std::process::exit(129);
+                    }
+                }
+            }
+            arg if arg.starts_with("--output-directory=") => {
+                output_dir = arg["--output-directory=".len()..].to_string();
+            }
+            "--include-paths" => include_paths = true,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+        i += 1;
+    }
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    match diagnose(&repo, Path::new(&output_dir), include_paths) {
+        Ok(dir) => println!("Created diagnostics directory at '{}'", dir.display()),
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+// Write a directory of reports that describe the setup and repository shape without
+// revealing contents: no object data, no credentials and, unless asked, no file names
+fn diagnose(repo: &Repository, output_dir: &Path, include_paths: bool) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dir = output_dir.join(format!("rakke-diagnostics-{}", timestamp));
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("could not create directory '{}': {}", dir.display(), e))?;
+    
+    let config = Config::load(repo)?;
+    let index = Index::load(index::index_path(repo, env::var_os("GIT_INDEX_FILE")))?;
+    
+    write_report(&dir, "environment.txt", &environment_report())?;
+    write_report(&dir, "config.txt", &config_report(&config))?;
+    write_report(&dir, "repository.txt", &repository_report(repo, &index)?)?;
+    
+    if include_paths {
+        let listing: String = index.entries()
+            .map(|entry| format!("{:o} {}\n", entry.mode, String::from_utf8_lossy(&paths::quote_c_style(&entry.path, true))))
+            .collect();
+        write_report(&dir, "paths.txt", &listing)?;
+    }
+    
+    Ok(dir)
+}
+
+fn write_report(dir: &Path, name: &str, content: &str) -> Result<(), String> {
+    fs::write(dir.join(name), content)
+        .map_err(|e| format!("could not write '{}': {}", name, e))
+}
+
+fn environment_report() -> String {
+    let mut report = String::new();
+    report.push_str(&format!("rakke version {}\n", env!("CARGO_PKG_VERSION")));
+    report.push_str(&format!("os: {}\n", env::consts::OS));
+    report.push_str(&format!("arch: {}\n", env::consts::ARCH));
+    report.push_str(&format!("family: {}\n", env::consts::FAMILY));
+    
+    // Only whether identity overrides are set, never their values
+    for var in ["GIT_DIR", "GIT_INDEX_FILE", "GIT_AUTHOR_NAME", "GIT_COMMITTER_NAME"] {
+        let state = if env::var_os(var).is_some() { "set" } else { "unset" };
+        report.push_str(&format!("{}: {}\n", var, state));
+    }
+    
+    report
+}
+
+// Config with credentials removed: secret-looking values are replaced and URLs,
+// which may appear in both keys (url.<base>.insteadOf) and values, lose their userinfo
+fn config_report(config: &Config) -> String {
+    config.entries()
+        .iter()
+        .map(|(key, value)| {
+            let value = if is_secret_key(key) {
+                "<redacted>".to_string()
+            } else {
+                strip_userinfo(value)
+            };
+            format!("{}={}\n", strip_userinfo(key), value)
+        })
+        .collect()
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    ["password", "token", "secret", "extraheader", "cookiefile", "helper"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+// Remove "user:password@" from every URL in a string
+fn strip_userinfo(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    
+    while let Some(pos) = rest.find("://") {
+        let (before, after) = rest.split_at(pos + 3);
+        result.push_str(before);
+        
+        // Userinfo ends at the last '@' before the host part of the URL
+        let authority_end = after.find(['/', ' ', '"']).unwrap_or(after.len());
+        match after[..authority_end].rfind('@') {
+            Some(at) => {
+                result.push_str("<redacted>@");
+                rest = &after[at + 1..];
+            }
+            None => rest = after,
+        }
+    }
+    
+    result.push_str(rest);
+    result
+}
+
+// Counts and sizes only; object ids are fine to share, their contents are not
+fn repository_report(repo: &Repository, index: &Index) -> Result<String, String> {
+    let mut report = String::new();
+    
+    // Loose objects by type, read from their headers alone, plus the largest ones on disk
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut sizes: Vec<(u64, String)> = Vec::new();
+    for hash in fsck::loose_objects(repo)? {
+        let kind = match objects::read_object_header(repo, &hash) {
+            Ok((kind, _)) => kind,
+            Err(_) => "corrupt".to_string(),
+        };
+        *counts.entry(kind).or_insert(0) += 1;
+        
+        let path = repo.git_path(&format!("objects/{}/{}", &hash[..2], &hash[2..]));
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        sizes.push((size, hash));
+    }
+    
+    report.push_str(&format!("loose objects: {}\n", sizes.len()));
+    for (kind, count) in &counts {
+        report.push_str(&format!("  {}: {}\n", kind, count));
+    }
+    
+    sizes.sort_by(|a, b| b.cmp(a));
+    report.push_str("largest loose objects (compressed bytes):\n");
+    for (size, hash) in sizes.iter().take(10) {
+        report.push_str(&format!("  {} {}\n", hash, size));
+    }
+    
+    // Packs can't be read yet, but their indexes tell how many objects they hold
+    let packs = list_files(&repo.git_path("objects/pack"))?;
+    let pack_files: Vec<&(String, u64)> = packs.iter().filter(|(name, _)| name.ends_with(".pack")).collect();
+    let pack_bytes: u64 = pack_files.iter().map(|(_, size)| size).sum();
+    let packed_objects = fsck::packed_objects(repo)?.len();
+    report.push_str(&format!("packs: {} ({} bytes, {} objects)\n", pack_files.len(), pack_bytes, packed_objects));
+    
+    report.push_str(&format!("loose refs: {}\n", count_files(&repo.git_path("refs"))?));
+    let packed_refs = fs::read_to_string(repo.git_path("packed-refs"))
+        .map(|content| content.lines().filter(|line| !line.starts_with('#') && !line.starts_with('^')).count())
+        .unwrap_or(0);
+    report.push_str(&format!("packed refs: {}\n", packed_refs));
+    
+    report.push_str(&format!("index entries: {}\n", index.entries().count()));
+    
+    // What git fsck would find, as far as loose objects go
+    let connectivity = fsck::check_connectivity(repo, index)?;
+    report.push_str("connectivity from HEAD, refs and the index:\n");
+    report.push_str(&format!("  reachable loose objects: {}\n", connectivity.reachable));
+    report.push_str(&format!("  reachable packed objects (not followed): {}\n", connectivity.packed));
+    report.push_str(&format!("  unreachable loose objects: {}\n", connectivity.unreachable.len()));
+    for (label, hashes) in [("missing", &connectivity.missing), ("corrupt", &connectivity.corrupt)] {
+        report.push_str(&format!("  {} objects: {}\n", label, hashes.len()));
+        for hash in hashes.iter().take(10) {
+            report.push_str(&format!("    {}\n", hash));
+        }
+    }
+    
+    Ok(report)
+}
+
+// Names and sizes of the entries of a directory; a missing directory is empty
+fn list_files(dir: &Path) -> Result<Vec<(String, u64)>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| format!("Cannot read directory entry: {}", e))?;
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        files.push((entry.file_name().to_string_lossy().to_string(), size));
+    }
+    
+    Ok(files)
+}
+
+// Number of files below a directory, recursively
+fn count_files(dir: &Path) -> Result<usize, String> {
+    let mut count = 0;
+    
+    for (name, _) in list_files(dir)? {
+        let path = dir.join(name);
+        if path.is_dir() {
+            count += count_files(&path)?;
+        } else {
+            count += 1;
+        }
+    }
+    
+    Ok(count)
+}
+
+fn print_help() {
+    println!("usage: rakke diagnose [(-o | --output-directory) <path>] [--include-paths]");
+    println!();
+    println!("    -o, --output-directory <path>");
+    println!("                          specify a destination for the diagnostics directory");
+    println!("    --include-paths       also list the paths of staged files");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
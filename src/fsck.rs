@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use crate::hex;
+use crate::index::Index;
+use crate::objects;
+use crate::refs;
+use crate::repository::Repository;
+
+// What a walk from HEAD, the refs and the index found, in the spirit of git fsck. Packs are
+// only indexed, not read, so objects found in them are counted but not followed further.
+#[derive(Debug, Default)]
+pub struct Connectivity {
+    pub reachable: usize,         // Loose objects reached from a root
+    pub packed: usize,            // Reached objects stored only in a pack
+    pub missing: Vec<String>,     // Referenced objects found nowhere
+    pub corrupt: Vec<String>,     // Reached loose objects that could not be read
+    pub unreachable: Vec<String>, // Loose objects no root leads to
+}
+
+// Follow every link from the roots through the loose objects
+pub fn check_connectivity(repo: &Repository, index: &Index) -> Result<Connectivity, String> {
+    let loose: HashSet<String> = loose_objects(repo)?.into_iter().collect();
+    let packed = packed_objects(repo)?;
+    let mut connectivity = Connectivity::default();
+    let mut seen = HashSet::new();
+    let mut pending = roots(repo, index);
+    
+    while let Some(hash) = pending.pop() {
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+        if !loose.contains(&hash) {
+            if packed.contains(&hash) {
+                connectivity.packed += 1;
+            } else {
+                connectivity.missing.push(hash);
+            }
+            continue;
+        }
+        connectivity.reachable += 1;
+        
+        // Blobs link to nothing, so their header is all that needs inflating
+        match objects::read_object_header(repo, &hash) {
+            Ok((kind, _)) if kind == "blob" => continue,
+            Ok(_) => {}
+            Err(_) => {
+                connectivity.corrupt.push(hash);
+                continue;
+            }
+        }
+        match objects::read_object(repo, &hash).and_then(|(kind, content)| links(&kind, &content)) {
+            Ok(links) => pending.extend(links),
+            Err(_) => connectivity.corrupt.push(hash),
+        }
+    }
+    
+    connectivity.unreachable = loose.into_iter().filter(|hash| !seen.contains(hash)).collect();
+    connectivity.missing.sort();
+    connectivity.corrupt.sort();
+    connectivity.unreachable.sort();
+    Ok(connectivity)
+}
+
+// HEAD, every loose and packed ref, and the objects staged in the index
+fn roots(repo: &Repository, index: &Index) -> Vec<String> {
+    let mut roots = Vec::new();
+    
+    // A ref that can't be resolved is left to the rest of the report, not an error here
+    let mut names = vec!["HEAD".to_string()];
+    loose_ref_names(&repo.git_path("refs"), "refs", &mut names);
+    names.extend(packed_ref_names(repo));
+    roots.extend(names.iter().filter_map(|name| refs::read_ref(repo, name).ok().flatten()));
+    
+    // Submodule commits live in another repository
+    roots.extend(index.entries().filter(|entry| entry.mode != 0o160000).map(|entry| entry.hash.clone()));
+    roots
+}
+
+fn loose_ref_names(dir: &Path, prefix: &str, names: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if entry.path().is_dir() {
+            loose_ref_names(&entry.path(), &name, names);
+        } else {
+            names.push(name);
+        }
+    }
+}
+
+fn packed_ref_names(repo: &Repository) -> Vec<String> {
+    fs::read_to_string(repo.git_path("packed-refs"))
+        .map(|content| {
+            content.lines()
+                .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+                .filter_map(|line| line.split_once(' '))
+                .map(|(_, name)| name.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Objects a commit, tree or tag points to
+fn links(kind: &str, content: &[u8]) -> Result<Vec<String>, String> {
+    match kind {
+        "tree" => Ok(objects::parse_tree(content)?
+            .into_iter()
+            .filter(|entry| entry.mode != 0o160000)
+            .map(|entry| entry.hash)
+            .collect()),
+        "commit" | "tag" => Ok(String::from_utf8_lossy(content)
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.strip_prefix("tree ").or(line.strip_prefix("parent ")).or(line.strip_prefix("object ")))
+            .map(|hash| hash.to_string())
+            .collect()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+// Every loose object id, in no particular order
+pub fn loose_objects(repo: &Repository) -> Result<Vec<String>, String> {
+    let mut found = Vec::new();
+    let objects_dir = repo.git_path("objects");
+    
+    for dir in read_dir_names(&objects_dir)? {
+        if dir.len() != 2 || !dir.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        for file in read_dir_names(&objects_dir.join(&dir))? {
+            let hash = format!("{}{}", dir, file);
+            if objects::is_valid_hash(&hash) {
+                found.push(hash);
+            }
+        }
+    }
+    
+    Ok(found)
+}
+
+// Every object id listed by the pack indexes in objects/pack
+pub fn packed_objects(repo: &Repository) -> Result<HashSet<String>, String> {
+    let pack_dir = repo.git_path("objects/pack");
+    let mut found = HashSet::new();
+    
+    for name in read_dir_names(&pack_dir)? {
+        if name.ends_with(".idx") {
+            found.extend(read_pack_index(&pack_dir.join(name))?);
+        }
+    }
+    
+    Ok(found)
+}
+
+// The object ids of a pack index. Version 2 starts with a magic number and version; version 1
+// starts straight with the fan-out table, whose last entry is the number of objects.
+pub fn read_pack_index(path: &Path) -> Result<Vec<String>, String> {
+    let data = fs::read(path)
+        .map_err(|e| format!("Cannot read pack index '{}': {}", path.display(), e))?;
+    let truncated = || format!("pack index '{}' is truncated", path.display());
+    
+    let version = match data.strip_prefix(b"\xfftOc") {
+        Some(rest) if rest.starts_with(&2u32.to_be_bytes()) => 2,
+        Some(_) => return Err(format!("pack index '{}' has an unsupported version", path.display())),
+        None => 1,
+    };
+    let fanout = if version == 2 { 8 } else { 0 };
+    let count = data.get(fanout + 1020..fanout + 1024).ok_or_else(truncated)?;
+    let count = u32::from_be_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    
+    // Version 2 lists the names together; version 1 gives each a 4-byte offset first
+    let (stride, skip) = if version == 2 { (20, 0) } else { (24, 4) };
+    (0..count)
+        .map(|i| {
+            let start = fanout + 1024 + i * stride + skip;
+            data.get(start..start + 20).map(hex::encode).ok_or_else(truncated)
+        })
+        .collect()
+}
+
+// Entry names of a directory; a missing directory is empty
+fn read_dir_names(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    
+    entries
+        .map(|entry| {
+            entry
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .map_err(|e| format!("Cannot read directory entry: {}", e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    
+    // A pack index naming the given objects, in either format
+    fn pack_index(version: u32, names: &[[u8; 20]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        if version == 2 {
+            data.extend_from_slice(b"\xfftOc");
+            data.extend_from_slice(&2u32.to_be_bytes());
+        }
+        for first in 0..=255u8 {
+            let below = names.iter().filter(|name| name[0] <= first).count() as u32;
+            data.extend_from_slice(&below.to_be_bytes());
+        }
+        for (i, name) in names.iter().enumerate() {
+            if version == 1 {
+                data.extend_from_slice(&(i as u32 * 100).to_be_bytes());
+            }
+            data.extend_from_slice(name);
+        }
+        data
+    }
+    
+    #[test]
+    fn pack_indexes_of_both_versions_list_their_objects() {
+        let names = [[0x01; 20], [0x7f; 20], [0xfe; 20]];
+        let path = env::temp_dir().join(format!("rakke-fsck-{}.idx", std::process::id()));
+        
+        for version in [1, 2] {
+            fs::write(&path, pack_index(version, &names)).unwrap();
+            let listed = read_pack_index(&path).unwrap();
+            assert_eq!(listed, names.iter().map(|name| hex::encode(name)).collect::<Vec<_>>());
+            
+            // The fan-out promises more names than the file holds
+            let data = pack_index(version, &names);
+            fs::write(&path, &data[..data.len() - 1]).unwrap();
+            assert!(read_pack_index(&path).is_err());
+        }
+        let _ = fs::remove_file(&path);
+    }
+}
\ No newline at end of file
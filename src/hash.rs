@@ -0,0 +1,196 @@
+use crate::hex;
+
+// Incremental SHA-1 hasher for git objects and index checksums
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: [u32; 5],   // Running hash values h0..h4
+    buffer: Vec<u8>,   // Bytes not yet forming a full 64-byte block
+    length: u64,       // Total number of bytes hashed so far
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1::new()
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Sha1 {
+        Sha1 {
+            state: [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0],
+            buffer: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+    
+    // Feed more data into the hash
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.length += data.len() as u64;
+        let mut data = data;
+        
+        // Complete a partially filled block first
+        if !self.buffer.is_empty() {
+            let needed = 64 - self.buffer.len();
+            let take = needed.min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            
+            if self.buffer.len() < 64 {
+                return self;
+            }
+            
+            let block = std::mem::take(&mut self.buffer);
+            self.process_block(&block);
+        }
+        
+        // Process full blocks straight from the input
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            self.process_block(block);
+        }
+        
+        // Keep the remainder for the next update
+        self.buffer.extend_from_slice(blocks.remainder());
+        self
+    }
+    
+    // Finish hashing and return the 20-byte digest
+    pub fn finalize(mut self) -> [u8; 20] {
+        let bit_len = self.length * 8;
+        
+        // Append '1' bit (0x80 byte), then zeros until length ≡ 448 (mod 512)
+        let mut padding = vec![0x80u8];
+        while !(self.buffer.len() + padding.len() + 8).is_multiple_of(64) {
+            padding.push(0);
+        }
+        
+        // Append original length as 64-bit big-endian
+        padding.extend_from_slice(&bit_len.to_be_bytes());
+        self.update(&padding);
+        
+        let mut digest = [0u8; 20];
+        for (i, word) in self.state.iter().enumerate() {
+            digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+    
+    // Finish hashing and return the digest as 40 hex digits
+    pub fn finalize_hex(self) -> String {
+        hex::encode(&self.finalize())
+    }
+    
+    // Process one 512-bit chunk
+    fn process_block(&mut self, chunk: &[u8]) {
+        let mut w = [0u32; 80];
+        
+        // Break chunk into sixteen 32-bit words
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        
+        // Extend words
+        for i in 16..80 {
+            w[i] = (w[i-3] ^ w[i-8] ^ w[i-14] ^ w[i-16]).rotate_left(1);
+        }
+        
+        // Initialize hash values for this chunk
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        
+        // Main loop
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | ((!b) & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                60..=79 => (b ^ c ^ d, 0xCA62C1D6),
+                _ => unreachable!(),
+            };
+            
+            let temp = a.rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        
+        // Add this chunk's hash to result
+        for (h, value) in self.state.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(value);
+        }
+    }
+}
+
+// Calculate the SHA-1 hash of a complete buffer as hex
+pub fn sha1_hash(data: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hasher.finalize_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Vectors from FIPS 180-2, plus git's well-known empty blob
+    #[test]
+    fn matches_the_standard_vectors() {
+        assert_eq!(sha1_hash(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(sha1_hash(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            sha1_hash(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(sha1_hash(b"blob 0\0"), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    }
+    
+    // Feeding data in uneven pieces must give the same result as hashing it at once
+    #[test]
+    fn incremental_updates_match_one_shot_hashing() {
+        let mut hasher = Sha1::new();
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            hasher.update(&chunk);
+        }
+        assert_eq!(hasher.finalize_hex(), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+        
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut hasher = Sha1::new();
+        for piece in data.chunks(63) {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finalize_hex(), sha1_hash(&data));
+    }
+    
+    // Blobs of 'x' repeated, with sizes around the 64-byte block and padding boundaries,
+    // as printed by `head -c <size> /dev/zero | tr '\0' x | git hash-object --stdin`
+    #[test]
+    fn blob_ids_match_git_hash_object() {
+        let expected = [
+            (0, "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+            (1, "c1b0730e0133447badcfd47fd144e254807b06e1"),
+            (46, "1d98a4e287acc725ef6f635b729014f7d49148da"),
+            (47, "719ddd7888a02660234770b651274214ff7df239"),
+            (55, "e329c5109ce10a7367561757d2f5a053c528f11e"),
+            (56, "355e203863c368f0404d7fc7ae4761122c117dd2"),
+            (57, "f32768e30e0507780e5b9edc8afa9d3f1009926c"),
+            (63, "6529afa7528d02472056dd24b8348542a4ad65b0"),
+            (64, "2036367ceafc6ad317a509db8f6b9c2773f731e0"),
+            (65, "73b921fe91b47e50aa0bd6a34e97bca293d9c7da"),
+            (119, "476be27639daf140e142c5f18280c33563ade72b"),
+            (1000, "14c7dfdd4258dec5c0e9d2e919bd249bd674be1f"),
+            (100000, "56e0448612acbb706b96b7e8e46a210f15386a38"),
+        ];
+        for (size, id) in expected {
+            let mut hasher = Sha1::new();
+            hasher.update(format!("blob {}\0", size).as_bytes()).update(&vec![b'x'; size]);
+            assert_eq!(hasher.finalize_hex(), id, "blob of {} bytes", size);
+        }
+    }
+}
\ No newline at end of file
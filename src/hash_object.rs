@@ -0,0 +1,99 @@
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use rakke::objects;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<OsString>) {
+    let mut kind = "blob".to_string();
+    let mut write = false;
+    let mut stdin = false;
+    let mut files: Vec<PathBuf> = Vec::new();
+    
+    // Process arguments (skip "hash-object" command itself); file names need not be UTF-8
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].to_str().unwrap_or("") {
+            "-w" => write = true,
+            "--stdin" => stdin = true,
+            "-t" => {
+                // Object type is given in the next argument
+                i += 1;
+                match args.get(i) {
+                    Some(value) => kind = value.to_string_lossy().into_owned(),
+                    None => {
+                        eprintln!("error: switch 't' requires a value");
+                        std::process::exit(129);
+                    }
+                }
+            }
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            "--" => {
+                // Everything after "--" is a file, even if it starts with '-'
+                files.extend(args[i + 1..].iter().map(PathBuf::from));
+                break;
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+            _ => files.push(PathBuf::from(&args[i])),
+        }
+        i += 1;
+    }
+    
+    if !matches!(kind.as_str(), "blob" | "tree" | "commit" | "tag") {
+        eprintln!("fatal: invalid object type \"{}\"", kind);
+        std::process::exit(128);
+    }
+    
+    // Only writing needs a repository; hashing works anywhere
+    let repo = if write {
+        match Repository::open(".") {
+            Ok(repo) => Some(repo),
+            Err(e) => {
+                eprintln!("fatal: {}", e);
+                std::process::exit(128);
+            }
+        }
+    } else {
+        None
+    };
+    
+    // Standard input comes first, then the files in the order given
+    if stdin {
+        let mut content = Vec::new();
+        if let Err(e) = io::stdin().read_to_end(&mut content) {
+            eprintln!("fatal: could not read from stdin: {}", e);
+            std::process::exit(128);
+        }
+        print_hash(objects::hash_content(&kind, &content, repo.as_ref()));
+    }
+    
+    for file in &files {
+        print_hash(objects::hash_file(file, &kind, repo.as_ref()));
+    }
+}
+
+fn print_hash(result: Result<String, String>) {
+    match result {
+        Ok(hash) => println!("{}", hash),
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+fn print_help() {
+    println!("usage: rakke hash-object [-t <type>] [-w] [--stdin] [--] <file>...");
+    println!();
+    println!("    -t <type>             object type");
+    println!("    -w                    write the object into the object database");
+    println!("    --stdin               read the object from stdin");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
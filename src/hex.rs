@@ -0,0 +1,76 @@
+// Decode a hexadecimal string such as an object id into raw bytes
+pub fn decode(hex: &str) -> Result<Vec<u8>, String> {
+    // Every byte needs exactly two digits
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Invalid hex string '{}': odd length {}", hex, hex.len()));
+    }
+    
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+    
+    // Process hex string in pairs of digits
+    for (i, pair) in hex.as_bytes().chunks(2).enumerate() {
+        let high = digit_value(pair[0])
+            .ok_or_else(|| invalid_digit(hex, i * 2))?;
+        let low = digit_value(pair[1])
+            .ok_or_else(|| invalid_digit(hex, i * 2 + 1))?;
+        bytes.push((high << 4) | low);
+    }
+    
+    Ok(bytes)
+}
+
+// Encode raw bytes as lowercase hexadecimal, the form object ids are written in
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn digit_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn invalid_digit(hex: &str, position: usize) -> String {
+    // Report the offending character, which may be part of a multi-byte sequence
+    let shown = hex.get(position..)
+        .and_then(|rest| rest.chars().next())
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| format!("\\x{:02x}", hex.as_bytes()[position]));
+    format!("Invalid hex string '{}': bad digit '{}' at position {}", hex, shown, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn round_trips_object_ids() {
+        let id = "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391";
+        let bytes = decode(id).unwrap();
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(encode(&bytes), id);
+        assert_eq!(decode("00FFaB").unwrap(), vec![0x00, 0xff, 0xab]);
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+    
+    #[test]
+    fn odd_length_is_rejected() {
+        assert_eq!(decode("abc").unwrap_err(), "Invalid hex string 'abc': odd length 3");
+        assert!(decode("e69de29bb2d1d6434b8b29ae775ad8c2e48c539").is_err());
+    }
+    
+    #[test]
+    fn non_hex_digits_are_rejected() {
+        assert_eq!(decode("0g").unwrap_err(), "Invalid hex string '0g': bad digit 'g' at position 1");
+        assert_eq!(decode("zz00").unwrap_err(), "Invalid hex string 'zz00': bad digit 'z' at position 0");
+        assert!(decode("12 4").is_err());
+        assert!(decode("+1").is_err());
+        
+        // A multi-byte character is reported whole, not as half of its encoding
+        assert_eq!(decode("\u{e9}00").unwrap_err(), "Invalid hex string '\u{e9}00': bad digit '\u{e9}' at position 0");
+        assert_eq!(decode("0\u{e9}0").unwrap_err(), "Invalid hex string '0\u{e9}0': bad digit '\u{e9}' at position 1");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::env;
+use std::time::SystemTime;
+use crate::config::Config;
+
+// Who made a change and when, as recorded in commit and tag headers
+#[derive(Debug, Clone)]
+pub struct Ident {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    pub timezone: String, // Offset such as "+0100"
+}
+
+impl Ident {
+    // Format as "Name <email> 1700000000 +0000" for object headers
+    pub fn to_header(&self) -> String {
+        format!("{} <{}> {} {}", self.name, self.email, self.timestamp, self.timezone)
+    }
+}
+
+// Which identity to resolve; each has its own GIT_*_NAME/EMAIL/DATE overrides
+#[derive(Debug, Clone, Copy)]
+pub enum Role {
+    Author,
+    Committer,
+}
+
+// Resolve an identity from the environment, falling back to user.name/user.email
+pub fn resolve(role: Role, config: &Config) -> Result<Ident, String> {
+    let prefix = match role {
+        Role::Author => "GIT_AUTHOR",
+        Role::Committer => "GIT_COMMITTER",
+    };
+    
+    let name = env::var(format!("{}_NAME", prefix)).ok()
+        .or_else(|| config.get("user.name").map(|s| s.to_string()))
+        .unwrap_or_default();
+    let email = env::var(format!("{}_EMAIL", prefix)).ok()
+        .or_else(|| config.get("user.email").map(|s| s.to_string()))
+        .unwrap_or_default();
+    
+    // Angle brackets and newlines would corrupt the header line
+    let name = sanitize(&name);
+    let email = sanitize(&email);
+    
+    if name.is_empty() {
+        return Err(format!(
+            "empty ident name (for <{}>) not allowed; set user.name or {}_NAME",
+            email, prefix
+        ));
+    }
+    if email.is_empty() {
+        return Err(format!("unable to auto-detect email address; set user.email or {}_EMAIL", prefix));
+    }
+    
+    let (timestamp, timezone) = match env::var(format!("{}_DATE", prefix)) {
+        Ok(date) => parse_date(&date)
+            .ok_or_else(|| format!("invalid date format: {}", date))?,
+        Err(_) => (now(), "+0000".to_string()),
+    };
+    
+    Ok(Ident { name, email, timestamp, timezone })
+}
+
+// Current time as seconds since the epoch; the local offset isn't available without libc, so UTC is recorded
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Parse git's internal date format "<seconds> <+hhmm>", optionally prefixed with '@'
+fn parse_date(date: &str) -> Option<(i64, String)> {
+    let date = date.trim();
+    let date = date.strip_prefix('@').unwrap_or(date);
+    
+    let (seconds, zone) = match date.split_once(' ') {
+        Some((seconds, zone)) => (seconds, zone.trim()),
+        None => (date, "+0000"),
+    };
+    
+    let timestamp = seconds.parse::<i64>().ok()?;
+    
+    // Offsets are a sign followed by exactly four digits
+    let valid_zone = zone.len() == 5
+        && (zone.starts_with('+') || zone.starts_with('-'))
+        && zone[1..].bytes().all(|b| b.is_ascii_digit());
+    if !valid_zone {
+        return None;
+    }
+    
+    Some((timestamp, zone.to_string()))
+}
+
+fn sanitize(value: &str) -> String {
+    value.chars()
+        .filter(|c| !matches!(c, '<' | '>' | '\n'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
\ No newline at end of file
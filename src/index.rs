@@ -0,0 +1,664 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use crate::hash::Sha1;
+use crate::hex;
+use crate::lockfile::LockedFile;
+use crate::objects::{self, TreeEntry};
+use crate::paths;
+use crate::repository::Repository;
+
+// Size of an entry's fixed fields: ten 32-bit stat fields, the 20-byte hash and 16-bit flags
+const ENTRY_HEADER_SIZE: usize = 62;
+
+// Path of the index file. Callers pass GIT_INDEX_FILE as the override, which can point
+// elsewhere (e.g. a temporary index); unset or empty means the repository's own index.
+pub fn index_path(repo: &Repository, override_path: Option<OsString>) -> PathBuf {
+    override_path
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| repo.git_path("index"))
+}
+
+// A single index entry; conflicted paths have one entry per stage (1-3)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: Vec<u8>, // Path relative to the repository root, as raw bytes
+    pub hash: String,  // SHA-1 hash of the file content
+    pub mode: u32,     // File permissions and type
+    pub size: u32,     // File size in bytes
+    pub mtime: u32,    // Last modification time
+    pub stage: u8,     // Merge stage, 0 for normal entries
+}
+
+// The staging area (.git/index), kept sorted by path and stage like git
+#[derive(Debug, Clone, Default)]
+pub struct Index {
+    entries: BTreeMap<(Vec<u8>, u8), Entry>,
+    timestamp: Option<u32>,     // Modification time of the file the index was loaded from
+    uptodate: HashSet<Vec<u8>>, // Paths staged since loading, whose stat data is fresh
+}
+
+impl Index {
+    pub fn new() -> Index {
+        Index::default()
+    }
+    
+    // Load an index file; a missing file is an empty index
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Index, String> {
+        let path = path.as_ref();
+        
+        // Return empty index if file doesn't exist yet
+        if !path.exists() {
+            return Ok(Index::new());
+        }
+        
+        // Read existing index file
+        let content = fs::read(path)
+            .map_err(|e| format!("Cannot read index file: {}", e))?;
+        
+        // Parse index file format
+        let mut index = parse_index(&content)?;
+        index.timestamp = fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .map(|mtime| mtime.as_secs() as u32);
+        Ok(index)
+    }
+    
+    // Serialize the index in git's version 2 format and write it to a file
+    pub fn write<P: AsRef<Path>>(&self, repo: &Repository, path: P) -> Result<(), String> {
+        let smudged = self.racily_changed(repo);
+        
+        // Write to index.lock so readers never see a half-written index
+        let mut lock = LockedFile::acquire(path)?;
+        
+        // Stream entries to disk instead of building the whole index in memory
+        serialize_index(self, &smudged, &mut lock)?;
+        lock.commit()
+    }
+    
+    // Entries modified in the same second the index was last written look clean by their
+    // stat data even if the file changed afterwards. Like git, check their content and return
+    // those that differ, so that their size is written as 0 and readers compare content.
+    fn racily_changed(&self, repo: &Repository) -> HashSet<&[u8]> {
+        let (Some(timestamp), Ok(work_tree)) = (self.timestamp, repo.work_tree()) else {
+            return HashSet::new();
+        };
+        
+        self.entries()
+            .filter(|entry| entry.stage == 0 && entry.size != 0 && entry.mtime >= timestamp)
+            .filter(|entry| entry.mode != 0o160000 && !self.uptodate.contains(&entry.path))
+            .filter(|entry| !matches_worktree(work_tree, entry))
+            .map(|entry| entry.path.as_slice())
+            .collect()
+    }
+    
+    // All entries, sorted by path and then stage
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+    
+    // Look up the normal (stage 0) entry for a path
+    pub fn get(&self, path: &[u8]) -> Option<&Entry> {
+        self.entries.get(&(path.to_vec(), 0))
+    }
+    
+    // Stage an entry at stage 0, resolving any conflict stages for its path
+    pub fn add(&mut self, mut entry: Entry) -> &mut Self {
+        self.remove(&entry.path);
+        entry.stage = 0;
+        self.uptodate.insert(entry.path.clone());
+        self.entries.insert((entry.path.clone(), 0), entry);
+        self
+    }
+    
+    // Remove every stage of a path
+    pub fn remove(&mut self, path: &[u8]) -> &mut Self {
+        for stage in 0..=3 {
+            self.entries.remove(&(path.to_vec(), stage));
+        }
+        self
+    }
+    
+    // Write one tree object per directory for the staged entries, returning the root tree
+    pub fn write_tree(&self, repo: &Repository) -> Result<String, String> {
+        if let Some(entry) = self.entries().find(|entry| entry.stage != 0) {
+            return Err(format!("{}: unmerged ({})", String::from_utf8_lossy(&entry.path), entry.hash));
+        }
+        
+        let entries: Vec<&Entry> = self.entries().collect();
+        write_tree_level(repo, &entries, b"")
+    }
+    
+    // Record a conflict stage (1 = base, 2 = ours, 3 = theirs) for a path
+    pub fn set_stage(&mut self, path: &[u8], stage: u8, mut entry: Entry) -> &mut Self {
+        // A conflicted path has no stage 0 entry
+        self.entries.remove(&(path.to_vec(), 0));
+        entry.path = path.to_vec();
+        entry.stage = stage;
+        self.entries.insert((path.to_vec(), stage), entry);
+        self
+    }
+}
+
+// Parse an index file in git's version 2, 3 or 4 format
+fn parse_index(content: &[u8]) -> Result<Index, String> {
+    // Header (signature, version, entry count) and trailing checksum are always present
+    if content.len() < 12 + 20 {
+        return Err("index file smaller than expected".to_string());
+    }
+    
+    // The last 20 bytes are the SHA-1 of everything before them
+    let (body, checksum) = content.split_at(content.len() - 20);
+    let mut hasher = Sha1::new();
+    hasher.update(body);
+    if hasher.finalize() != checksum {
+        return Err("bad index file sha1 signature".to_string());
+    }
+    
+    if &body[..4] != b"DIRC" {
+        return Err(format!("bad signature 0x{}", hex::encode(&body[..4])));
+    }
+    
+    // Version 3 adds extended flags, version 4 prefix-compresses paths; rakke writes version 2
+    let version = BigEndian::read_u32(&body[4..8]);
+    if !(2..=4).contains(&version) {
+        return Err(format!("index file version {} is not supported", version));
+    }
+    
+    // Read each entry, which starts right after the previous one
+    let count = BigEndian::read_u32(&body[8..12]);
+    let mut index = Index::new();
+    let mut offset = 12;
+    let mut previous_path = Vec::new();
+    for _ in 0..count {
+        let (entry, entry_len) = parse_index_entry(&body[offset..], version, &previous_path)?;
+        previous_path = entry.path.clone();
+        index.entries.insert((entry.path.clone(), entry.stage), entry);
+        offset += entry_len;
+    }
+    
+    skip_extensions(&body[offset..])?;
+    
+    Ok(index)
+}
+
+// Walk the extensions after the entries. Optional ones (uppercase signature, such as git's
+// cached trees in "TREE") are dropped, since rakke rewrites the index without them;
+// anything else changes how entries must be read, so it is an error.
+fn skip_extensions(mut data: &[u8]) -> Result<(), String> {
+    while !data.is_empty() {
+        if data.len() < 8 {
+            return Err("index extension is truncated".to_string());
+        }
+        
+        let signature = &data[..4];
+        let size = BigEndian::read_u32(&data[4..8]) as usize;
+        if data.len() - 8 < size {
+            return Err("index extension is truncated".to_string());
+        }
+        
+        if !signature[0].is_ascii_uppercase() {
+            return Err(format!(
+                "index uses {} extension, which we do not understand",
+                String::from_utf8_lossy(signature)
+            ));
+        }
+        
+        data = &data[8 + size..];
+    }
+    
+    Ok(())
+}
+
+// Parse one entry from the start of data, returning it and its length including padding
+fn parse_index_entry(data: &[u8], version: u32, previous_path: &[u8]) -> Result<(Entry, usize), String> {
+    if data.len() < ENTRY_HEADER_SIZE {
+        return Err("index entry is truncated".to_string());
+    }
+    
+    // Of the stat data only the modification time is kept; ctime, device, inode,
+    // uid and gid are written back as rakke always writes them
+    let mtime = BigEndian::read_u32(&data[8..12]);
+    let mode = BigEndian::read_u32(&data[24..28]);
+    let size = BigEndian::read_u32(&data[36..40]);
+    let hash = hex::encode(&data[40..60]);
+    let flags = BigEndian::read_u16(&data[60..62]);
+    let mut header_len = ENTRY_HEADER_SIZE;
+    
+    // Extended flags only exist from version 3 on and add two more bytes
+    if flags & 0x4000 != 0 {
+        if version < 3 {
+            return Err("index entry has extended flags in a version 2 index".to_string());
+        }
+        if data.len() < header_len + 2 {
+            return Err("index entry is truncated".to_string());
+        }
+        
+        // Dropping intent-to-add or skip-worktree would silently change what gets committed
+        if BigEndian::read_u16(&data[header_len..header_len + 2]) != 0 {
+            return Err("index entries marked intent-to-add or skip-worktree are not supported".to_string());
+        }
+        header_len += 2;
+    }
+    
+    let mut rest = &data[header_len..];
+    let mut path = Vec::new();
+    
+    // Version 4 paths are stored as "drop N bytes from the previous path, then append this"
+    if version == 4 {
+        let (strip, varint_len) = decode_varint(rest)
+            .ok_or("index entry is truncated")?;
+        let keep = previous_path.len().checked_sub(strip)
+            .ok_or("index entry strips more than the previous path")?;
+        path.extend_from_slice(&previous_path[..keep]);
+        rest = &rest[varint_len..];
+    }
+    
+    // The rest of the path is NUL-terminated
+    let suffix_len = rest.iter().position(|&b| b == 0)
+        .ok_or("index entry path is not terminated")?;
+    path.extend_from_slice(&rest[..suffix_len]);
+    
+    // The flags also carry the full path length, unless it is 0xfff or more
+    let flags_len = (flags & 0xfff) as usize;
+    if flags_len != path.len().min(0xfff) {
+        return Err("index entry path length does not match its flags".to_string());
+    }
+    
+    // Before version 4, entries are padded with 1-8 NUL bytes to a multiple of 8
+    let entry_len = if version == 4 {
+        data.len() - rest.len() + suffix_len + 1
+    } else {
+        (header_len + suffix_len + 8) & !7
+    };
+    if data.len() < entry_len {
+        return Err("index entry is truncated".to_string());
+    }
+    
+    let entry = Entry {
+        path,
+        hash,
+        mode,
+        size,
+        mtime,
+        stage: ((flags >> 12) & 0x3) as u8,
+    };
+    Ok((entry, entry_len))
+}
+
+// Decode git's offset varint: 7 bits per byte, most significant first, with each
+// continuation adding one so that every value has a single encoding
+fn decode_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut value: usize = 0;
+    
+    for (i, &byte) in data.iter().enumerate() {
+        if i > 0 {
+            value = value.checked_add(1)?.checked_mul(128)?;
+        }
+        value |= (byte & 0x7f) as usize;
+        
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    
+    None
+}
+
+// Write the tree for entries that all start with prefix ("" for the root or "dir/").
+// Sorted paths keep each subdirectory's entries next to each other.
+fn write_tree_level(repo: &Repository, entries: &[&Entry], prefix: &[u8]) -> Result<String, String> {
+    let mut tree_entries = Vec::new();
+    let mut i = 0;
+    
+    while i < entries.len() {
+        let name = &entries[i].path[prefix.len()..];
+        
+        match name.iter().position(|&b| b == b'/') {
+            None => {
+                tree_entries.push(TreeEntry {
+                    mode: entries[i].mode,
+                    name: name.to_vec(),
+                    hash: entries[i].hash.clone(),
+                });
+                i += 1;
+            }
+            Some(slash) => {
+                // Everything under this directory becomes one subtree
+                let dir = &name[..slash];
+                let dir_prefix = [prefix, dir, b"/".as_slice()].concat();
+                let end = i + entries[i..].iter()
+                    .take_while(|entry| entry.path.starts_with(&dir_prefix))
+                    .count();
+                
+                tree_entries.push(TreeEntry {
+                    mode: 0o040000,
+                    name: dir.to_vec(),
+                    hash: write_tree_level(repo, &entries[i..end], &dir_prefix)?,
+                });
+                i = end;
+            }
+        }
+    }
+    
+    let content = objects::tree_content(&mut tree_entries)?;
+    objects::write_object(repo, "tree", &content)
+}
+
+// Writer that feeds everything it writes through SHA-1 for the trailing checksum
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha1,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Whether a file still has the content an entry records
+fn matches_worktree(work_tree: &Path, entry: &Entry) -> bool {
+    let Ok(path) = paths::from_bytes(&entry.path) else {
+        return false;
+    };
+    let path = work_tree.join(path);
+    
+    // A symlink's blob is its target
+    let content = if entry.mode == 0o120000 {
+        fs::read_link(&path).ok().and_then(|target| paths::to_bytes(&target).ok())
+    } else {
+        fs::read(&path).ok()
+    };
+    content.is_some_and(|content| objects::hash_object("blob", &content) == entry.hash)
+}
+
+// Serialize the index to a writer, appending the checksum of everything written; entries
+// in smudged are written with a size of 0
+fn serialize_index<W: Write>(index: &Index, smudged: &HashSet<&[u8]>, out: W) -> Result<(), String> {
+    let mut content = HashingWriter { inner: out, hasher: Sha1::new() };
+    
+    // Write git index file signature "DIRC" (DIRtory Cache)
+    content.write_all(b"DIRC")
+        .map_err(|e| format!("Cannot write signature: {}", e))?;
+    
+    // Write index format version (version 2)
+    content.write_u32::<BigEndian>(2)
+        .map_err(|e| format!("Cannot write version: {}", e))?;
+    
+    // Write total number of index entries
+    content.write_u32::<BigEndian>(index.entries.len() as u32)
+        .map_err(|e| format!("Cannot write entry count: {}", e))?;
+    
+    // Write each index entry, already sorted by path and stage
+    for entry in index.entries() {
+        if smudged.contains(entry.path.as_slice()) {
+            write_index_entry(&mut content, &Entry { size: 0, ..entry.clone() })?;
+        } else {
+            write_index_entry(&mut content, entry)?;
+        }
+    }
+    
+    // Append SHA-1 checksum of entire index
+    let checksum = content.hasher.finalize();
+    content.inner.write_all(&checksum)
+        .map_err(|e| format!("Cannot write checksum: {}", e))?;
+    content.inner.flush()
+        .map_err(|e| format!("Cannot write index file: {}", e))
+}
+
+fn write_index_entry<W: Write>(content: &mut W, entry: &Entry) -> Result<(), String> {
+    // Write creation time (set to modification time for simplicity)
+    content.write_u32::<BigEndian>(entry.mtime)
+        .map_err(|e| format!("Cannot write ctime: {}", e))?;
+    content.write_u32::<BigEndian>(0) // nanoseconds
+        .map_err(|e| format!("Cannot write ctime_ns: {}", e))?;
+    
+    // Write modification time
+    content.write_u32::<BigEndian>(entry.mtime)
+        .map_err(|e| format!("Cannot write mtime: {}", e))?;
+    content.write_u32::<BigEndian>(0) // nanoseconds
+        .map_err(|e| format!("Cannot write mtime_ns: {}", e))?;
+    
+    // Write device and inode (set to 0 for cross-platform compatibility)
+    content.write_u32::<BigEndian>(0) // device
+        .map_err(|e| format!("Cannot write device: {}", e))?;
+    content.write_u32::<BigEndian>(0) // inode
+        .map_err(|e| format!("Cannot write inode: {}", e))?;
+    
+    // Write file mode (permissions and file type)
+    content.write_u32::<BigEndian>(entry.mode)
+        .map_err(|e| format!("Cannot write mode: {}", e))?;
+    
+    // Write user and group IDs (set to 0 for simplicity)
+    content.write_u32::<BigEndian>(0) // uid
+        .map_err(|e| format!("Cannot write uid: {}", e))?;
+    content.write_u32::<BigEndian>(0) // gid
+        .map_err(|e| format!("Cannot write gid: {}", e))?;
+    
+    // Write file size
+    content.write_u32::<BigEndian>(entry.size)
+        .map_err(|e| format!("Cannot write size: {}", e))?;
+    
+    // Write SHA-1 hash (20 bytes)
+    let hash_bytes = hex::decode(&entry.hash)?;
+    if hash_bytes.len() != 20 {
+        return Err("Invalid SHA-1 hash length".to_string());
+    }
+    content.write_all(&hash_bytes)
+        .map_err(|e| format!("Cannot write hash: {}", e))?;
+    
+    // Write flags: merge stage in bits 12-13, path length in the low 12 bits
+    let path_len = std::cmp::min(entry.path.len(), 0xfff); // max 12 bits for path length
+    let flags = ((entry.stage as u16 & 0x3) << 12) | path_len as u16;
+    content.write_u16::<BigEndian>(flags)
+        .map_err(|e| format!("Cannot write flags: {}", e))?;
+    
+    // Write file path followed by 1-8 NUL bytes, padding the entry to a multiple of 8
+    let entry_len = ENTRY_HEADER_SIZE + entry.path.len();
+    let padding = 8 - entry_len % 8;
+    content.write_all(&entry.path)
+        .and_then(|_| content.write_all(&[0u8; 8][..padding]))
+        .map_err(|e| format!("Cannot write path: {}", e))?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    
+    fn entry(path: &[u8], hash_digit: char) -> Entry {
+        Entry {
+            path: path.to_vec(),
+            hash: hash_digit.to_string().repeat(40),
+            mode: 0o100644,
+            size: 12,
+            mtime: 1700000000,
+            stage: 0,
+        }
+    }
+    
+    fn serialize(index: &Index) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serialize_index(index, &HashSet::new(), &mut bytes).unwrap();
+        bytes
+    }
+    
+    #[test]
+    fn serialize_parse_serialize_round_trip() {
+        let mut index = Index::new();
+        index.add(entry(b"src/main.rs", 'a'))
+            .add(entry(b"README", 'b'))
+            .add(Entry { mode: 0o100755, ..entry(b"tool.sh", 'c') })
+            .add(Entry { mode: 0o120000, ..entry(b"link", 'd') })
+            .add(entry(b"removed", 'e'))
+            .remove(b"removed");
+        
+        let bytes = serialize(&index);
+        let parsed = parse_index(&bytes).unwrap();
+        assert_eq!(parsed.entries().cloned().collect::<Vec<_>>(), index.entries().cloned().collect::<Vec<_>>());
+        assert_eq!(serialize(&parsed), bytes);
+        
+        // Entries come back sorted by path, as git requires
+        let paths: Vec<&[u8]> = parsed.entries().map(|entry| entry.path.as_slice()).collect();
+        assert_eq!(paths, [b"README".as_slice(), b"link", b"src/main.rs", b"tool.sh"]);
+        assert_eq!(parsed.get(b"tool.sh").unwrap().mode, 0o100755);
+        assert!(parsed.get(b"removed").is_none());
+    }
+    
+    #[test]
+    fn empty_index_round_trips() {
+        let bytes = serialize(&Index::new());
+        assert_eq!(bytes.len(), 12 + 20);
+        assert_eq!(parse_index(&bytes).unwrap().entries().count(), 0);
+    }
+    
+    #[test]
+    fn every_padding_length_round_trips() {
+        // Path lengths 1 to 16 cover each of the eight possible paddings twice
+        let mut index = Index::new();
+        for len in 1..=16 {
+            index.add(entry("p".repeat(len).as_bytes(), 'f'));
+        }
+        
+        let bytes = serialize(&index);
+        let parsed = parse_index(&bytes).unwrap();
+        assert_eq!(parsed.entries().count(), 16);
+        for len in 1..=16 {
+            assert!(parsed.get("p".repeat(len).as_bytes()).is_some(), "path of length {}", len);
+        }
+        assert_eq!(serialize(&parsed), bytes);
+    }
+    
+    #[test]
+    fn conflict_stages_and_raw_paths_round_trip() {
+        let mut index = Index::new();
+        index.add(entry(b"caf\xe9", '1'))
+            .set_stage(b"both", 1, entry(b"", '2'))
+            .set_stage(b"both", 2, entry(b"", '3'))
+            .set_stage(b"both", 3, entry(b"", '4'));
+        
+        let bytes = serialize(&index);
+        let parsed = parse_index(&bytes).unwrap();
+        let stages: Vec<(Vec<u8>, u8)> = parsed.entries().map(|entry| (entry.path.clone(), entry.stage)).collect();
+        assert_eq!(stages, [(b"both".to_vec(), 1), (b"both".to_vec(), 2), (b"both".to_vec(), 3), (b"caf\xe9".to_vec(), 0)]);
+        assert!(parsed.get(b"both").is_none());
+        assert_eq!(serialize(&parsed), bytes);
+    }
+    
+    #[test]
+    fn adding_resolves_only_that_paths_stages() {
+        let mut index = Index::new();
+        for stage in 1..=3 {
+            index.set_stage(b"both", stage, entry(b"", '1'));
+            index.set_stage(b"both2", stage, entry(b"", '2'));
+        }
+        index.add(entry(b"both/inner", '3')).add(entry(b"both", '4'));
+        
+        let stages: Vec<(&[u8], u8)> = index.entries().map(|entry| (entry.path.as_slice(), entry.stage)).collect();
+        assert_eq!(stages, [(b"both".as_slice(), 0), (b"both/inner", 0), (b"both2", 1), (b"both2", 2), (b"both2", 3)]);
+        assert_eq!(index.get(b"both").unwrap().hash, "4".repeat(40));
+    }
+    
+    #[test]
+    fn damaged_index_is_rejected() {
+        let mut index = Index::new();
+        index.add(entry(b"file", 'a'));
+        let bytes = serialize(&index);
+        
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert_eq!(parse_index(&flipped).unwrap_err(), "bad index file sha1 signature");
+        assert!(parse_index(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_index(&bytes[..20]).is_err());
+    }
+    
+    // Accepts at most one byte per call, like a slow pipe making short writes
+    struct ByteAtATime(Vec<u8>);
+    
+    impl Write for ByteAtATime {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.extend_from_slice(&buf[..buf.len().min(1)]);
+            Ok(buf.len().min(1))
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[test]
+    fn streamed_output_matches_buffered() {
+        let mut index = Index::new();
+        for i in 0..100 {
+            index.add(entry(format!("dir{}/file{}", i % 7, i).as_bytes(), 'a'));
+        }
+        
+        // The whole index assembled in memory first, then hashed, as it used to be written
+        let mut buffered = b"DIRC".to_vec();
+        buffered.write_u32::<BigEndian>(2).unwrap();
+        buffered.write_u32::<BigEndian>(100).unwrap();
+        for entry in index.entries() {
+            write_index_entry(&mut buffered, entry).unwrap();
+        }
+        let mut hasher = Sha1::new();
+        hasher.update(&buffered);
+        let checksum = hasher.finalize();
+        buffered.extend_from_slice(&checksum);
+        
+        assert_eq!(serialize(&index), buffered);
+        
+        let mut short_writes = ByteAtATime(Vec::new());
+        serialize_index(&index, &HashSet::new(), &mut short_writes).unwrap();
+        assert_eq!(short_writes.0, buffered);
+        
+        // Writing through the lock file produces the same bytes on disk
+        let dir = env::temp_dir().join(format!("rakke-index-stream-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repo = Repository::init(&dir, false, "master").unwrap();
+        index.write(&repo, repo.git_path("index")).unwrap();
+        assert_eq!(fs::read(repo.git_path("index")).unwrap(), buffered);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn index_file_override_leaves_the_real_index_untouched() {
+        let dir = env::temp_dir().join(format!("rakke-index-override-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repo = Repository::init(&dir, false, "master").unwrap();
+        
+        let mut real = Index::new();
+        real.add(entry(b"real", 'a'));
+        real.write(&repo, index_path(&repo, None)).unwrap();
+        let before = fs::read(repo.git_path("index")).unwrap();
+        
+        let alternate = dir.join("alternate-index");
+        let path = index_path(&repo, Some(alternate.clone().into_os_string()));
+        let mut index = Index::load(&path).unwrap();
+        index.add(entry(b"temporary", 'b'));
+        index.write(&repo, &path).unwrap();
+        
+        // An empty value means the default index, like an unset one
+        assert_eq!(index_path(&repo, Some(OsString::new())), repo.git_path("index"));
+        
+        assert_eq!(path, alternate);
+        assert_eq!(fs::read(repo.git_path("index")).unwrap(), before);
+        let written = Index::load(&alternate).unwrap();
+        assert!(written.get(b"temporary").is_some());
+        assert!(written.get(b"real").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::fs;
+use std::io::{self, Read};
+use rakke::config::Config;
+use rakke::repository::Repository;
+use rakke::trailers::{self, PolicyOverrides, Trailer};
+
+pub fn execute(args: Vec<String>) {
+    let mut trailer_args: Vec<(String, PolicyOverrides)> = Vec::new();
+    let mut overrides = PolicyOverrides::default();
+    let mut in_place = false;
+    let mut parse_only = false;
+    let mut files: Vec<String> = Vec::new();
+    
+    // Process arguments (skip "interpret-trailers" command itself)
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        match arg {
+            "--in-place" => in_place = true,
+            "--parse" => parse_only = true,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            "--trailer" | "--where" | "--if-exists" | "--if-missing" => {
+                // Options taking a value in the next argument
+                i += 1;
+                let value = match args.get(i) {
+                    Some(value) => value.clone(),
+                    None => {
+                        eprintln!("error: option '{}' requires a value", &arg[2..]);
+                        std::process::exit(129);
+                    }
+                };
+                apply_option(arg, &value, &mut trailer_args, &mut overrides);
+            }
+            _ if arg.starts_with("--") && arg.contains('=') => {
+                let (name, value) = arg.split_once('=').unwrap();
+                apply_option(name, value, &mut trailer_args, &mut overrides);
+            }
+            _ if !arg.starts_with('-') => files.push(arg.to_string()),
+            _ => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+        i += 1;
+    }
+    
+    if let Err(e) = run(&trailer_args, in_place, parse_only, &files) {
+        eprintln!("fatal: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// Placement options only affect the "--trailer" arguments that follow them, as in git
+fn apply_option(name: &str, value: &str, trailer_args: &mut Vec<(String, PolicyOverrides)>, overrides: &mut PolicyOverrides) {
+    let valid = match name {
+        "--trailer" => {
+            trailer_args.push((value.to_string(), *overrides));
+            true
+        }
+        "--where" => trailers::parse_where(value).map(|v| overrides.place = Some(v)).is_some(),
+        "--if-exists" => trailers::parse_if_exists(value).map(|v| overrides.if_exists = Some(v)).is_some(),
+        "--if-missing" => trailers::parse_if_missing(value).map(|v| overrides.if_missing = Some(v)).is_some(),
+        _ => {
+            eprintln!("Unknown option: {}", name);
+            print_help();
+            std::process::exit(129);
+        }
+    };
+    
+    if !valid {
+        eprintln!("fatal: unknown value '{}' for option '{}'", value, &name[2..]);
+        std::process::exit(129);
+    }
+}
+
+fn run(trailer_args: &[(String, PolicyOverrides)], in_place: bool, parse_only: bool, files: &[String]) -> Result<(), String> {
+    let config = load_config()?;
+    
+    // Resolve "--trailer" arguments through any configured key aliases
+    let new_trailers: Vec<(Trailer, PolicyOverrides)> = trailer_args
+        .iter()
+        .map(|(arg, overrides)| Ok((trailers::parse_trailer_argument(arg, &config)?, *overrides)))
+        .collect::<Result<_, String>>()?;
+    
+    if files.is_empty() {
+        if in_place {
+            return Err("no input file given for in-place editing".to_string());
+        }
+        
+        // Read the message from stdin and write the result to stdout
+        let mut message = String::new();
+        io::stdin().read_to_string(&mut message)
+            .map_err(|e| format!("Cannot read from stdin: {}", e))?;
+        
+        print!("{}", process(&message, &new_trailers, &config, parse_only));
+        return Ok(());
+    }
+    
+    for file in files {
+        let message = fs::read_to_string(file)
+            .map_err(|e| format!("could not read input file '{}': {}", file, e))?;
+        
+        let output = process(&message, &new_trailers, &config, parse_only);
+        
+        if in_place {
+            fs::write(file, output)
+                .map_err(|e| format!("could not write to '{}': {}", file, e))?;
+        } else {
+            print!("{}", output);
+        }
+    }
+    
+    Ok(())
+}
+
+fn process(message: &str, new_trailers: &[(Trailer, PolicyOverrides)], config: &Config, parse_only: bool) -> String {
+    let output = trailers::add_trailers(message, new_trailers, config);
+    
+    if !parse_only {
+        return output;
+    }
+    
+    // Only print the trailers themselves, one unfolded "key: value" per line
+    trailers::parse_trailers(&output)
+        .iter()
+        .map(|t| format!("{}\n", trailers::format_trailer(t)))
+        .collect()
+}
+
+// Outside a repository only the global settings apply
+fn load_config() -> Result<Config, String> {
+    match Repository::open(".") {
+        Ok(repo) => Config::load(&repo),
+        Err(_) => Config::load_global(),
+    }
+}
+
+fn print_help() {
+    println!("usage: rakke interpret-trailers [--in-place] [--parse] [(--trailer <token>[(=|:)<value>])...] [<file>...]");
+    println!();
+    println!("    --in-place            edit files in place");
+    println!("    --parse               only output the trailers, with values unfolded");
+    println!("    --trailer <trailer>   trailer(s) to add");
+    println!("    --where <placement>   where to place the new trailer (end, start, after, before)");
+    println!("    --if-exists <action>  action if trailer already exists");
+    println!("    --if-missing <action> action if trailer is missing (add, doNothing)");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+// Repository internals shared by the rakke commands, usable without going through argv
+pub mod repository;
+pub mod config;
+pub mod refs;
+pub mod pathspec;
+pub mod paths;
+pub mod hex;
+pub mod hash;
+pub mod objects;
+pub mod fsck;
+pub mod lockfile;
+pub mod index;
+pub mod ident;
+pub mod trailers;
+pub mod message;
+pub mod wt_status;
\ No newline at end of file
@@ -0,0 +1,240 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// A lock whose owner can't be checked counts as stale once it is this old
+const STALE_LOCK_AGE: Duration = Duration::from_secs(600);
+
+// A file being rewritten through git's lock protocol: new content goes to "<path>.lock",
+// created exclusively so concurrent writers fail instead of interleaving. Committing renames
+// it over the original; dropping it uncommitted removes the lock and leaves the original alone.
+// As with git, nothing but the lock file itself is created, so its age is the only sign that a
+// crashed process left it behind.
+pub struct LockedFile {
+    path: PathBuf,
+    lock_path: PathBuf,
+    file: Option<BufWriter<File>>, // None once committed or rolled back
+    sync: bool,                    // fsync before the rename
+}
+
+impl LockedFile {
+    // Take the lock for path, failing if another process holds it. Stale locks are only
+    // broken when RAKKE_BREAK_STALE_LOCKS=1 is set.
+    pub fn acquire<P: AsRef<Path>>(path: P) -> Result<LockedFile, String> {
+        let break_stale = env::var_os("RAKKE_BREAK_STALE_LOCKS").is_some_and(|value| value == "1");
+        LockedFile::acquire_with(path, break_stale)
+    }
+    
+    // Take the lock for path; with break_stale, a lock untouched for STALE_LOCK_AGE is removed
+    // first. Only use this when no other process can be working on the file, since two
+    // processes that both judge a lock stale could each remove it and take it over.
+    pub fn acquire_with<P: AsRef<Path>>(path: P, break_stale: bool) -> Result<LockedFile, String> {
+        let path = path.as_ref().to_path_buf();
+        let lock_path = with_suffix(&path, ".lock");
+        
+        let file = match create_exclusive(&lock_path) {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => match stale_reason(&lock_path) {
+                Some(_) if break_stale => {
+                    let _ = fs::remove_file(&lock_path);
+                    create_exclusive(&lock_path)
+                }
+                stale => return Err(lock_error(&lock_path, e, stale)),
+            },
+            result => result,
+        }
+        .map_err(|e| lock_error(&lock_path, e, None))?;
+        
+        Ok(LockedFile {
+            path,
+            lock_path,
+            file: Some(BufWriter::new(file)),
+            sync: false,
+        })
+    }
+    
+    // Flush the new content to disk before it replaces the original
+    pub fn sync_on_commit(&mut self) -> &mut Self {
+        self.sync = true;
+        self
+    }
+    
+    // Replace the original file with the written content
+    pub fn commit(mut self) -> Result<(), String> {
+        let file = match self.file.take() {
+            Some(file) => file,
+            None => return Err(format!("lock on '{}' is no longer held", self.path.display())),
+        };
+        
+        let result = finish(file, self.sync)
+            .and_then(|_| fs::rename(&self.lock_path, &self.path))
+            .map_err(|e| format!("Cannot write '{}': {}", self.path.display(), e));
+        
+        // On failure the original is untouched; don't leave the lock behind
+        if result.is_err() {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+        result
+    }
+    
+    // Give up the lock, discarding everything written
+    pub fn rollback(mut self) {
+        self.release();
+    }
+    
+    fn release(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+impl Write for LockedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.file.as_mut() {
+            Some(file) => file.write(buf),
+            None => Err(io::Error::other("lock already released")),
+        }
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for LockedFile {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+// Write a whole file through a lock
+pub fn write_locked<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<(), String> {
+    let mut lock = LockedFile::acquire(&path)?;
+    lock.write_all(content)
+        .map_err(|e| format!("Cannot write '{}': {}", path.as_ref().display(), e))?;
+    lock.commit()
+}
+
+fn finish(file: BufWriter<File>, sync: bool) -> io::Result<()> {
+    let file = file.into_inner().map_err(|e| e.into_error())?;
+    if sync {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn create_exclusive(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+}
+
+// Why an existing lock looks abandoned, or None while its owner may still be using it.
+// Writing through a lock updates its modification time, so a lock in use stays recent.
+fn stale_reason(lock_path: &Path) -> Option<String> {
+    let age = fs::metadata(lock_path).and_then(|m| m.modified()).ok()?.elapsed().ok()?;
+    if age >= STALE_LOCK_AGE {
+        Some(format!("it was last written {} seconds ago", age.as_secs()))
+    } else {
+        None
+    }
+}
+
+fn lock_error(lock_path: &Path, e: io::Error, stale: Option<String>) -> String {
+    if e.kind() != ErrorKind::AlreadyExists {
+        return format!("Unable to create '{}': {}", lock_path.display(), e);
+    }
+    
+    if let Some(reason) = stale {
+        return format!(
+            "Unable to create '{}': File exists.\n\n\
+             The lock looks stale: {}.\n\
+             Set RAKKE_BREAK_STALE_LOCKS=1 to remove it and continue.",
+            lock_path.display(),
+            reason
+        );
+    }
+    
+    // Same advice as git, since a crashed process is the usual cause of a leftover lock
+    format!(
+        "Unable to create '{}': File exists.\n\n\
+         Another rakke process seems to be running in this repository.\n\
+         Please make sure all processes are terminated then try again.\n\
+         If it still fails, a rakke process may have crashed in this\n\
+         repository earlier: remove the file manually to continue.",
+        lock_path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+    use std::time::SystemTime;
+    
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rakke-lockfile-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn commit_replaces_the_file_and_removes_the_lock() {
+        let dir = scratch("commit");
+        let path = dir.join("file");
+        
+        let mut lock = LockedFile::acquire_with(&path, false).unwrap();
+        assert!(LockedFile::acquire_with(&path, true).is_err());
+        lock.write_all(b"new\n").unwrap();
+        lock.commit().unwrap();
+        
+        // Nothing is left beside the file, where git could mistake it for a ref
+        assert_eq!(fs::read(&path).unwrap(), b"new\n");
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names, ["file"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn old_lock_is_only_broken_on_request() {
+        let dir = scratch("old");
+        let path = dir.join("file");
+        fs::write(dir.join("file.lock"), b"partial").unwrap();
+        let long_ago = SystemTime::now() - STALE_LOCK_AGE - Duration::from_secs(60);
+        File::options().write(true).open(dir.join("file.lock")).unwrap().set_modified(long_ago).unwrap();
+        
+        let error = LockedFile::acquire_with(&path, false).err().unwrap();
+        assert!(error.contains("looks stale"), "{}", error);
+        
+        let lock = LockedFile::acquire_with(&path, true).unwrap();
+        lock.rollback();
+        assert!(!dir.join("file.lock").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn recent_lock_is_kept() {
+        let dir = scratch("recent");
+        let path = dir.join("file");
+        fs::write(dir.join("file.lock"), b"").unwrap();
+        
+        // A recent lock may still be in use, even by git
+        let error = LockedFile::acquire_with(&path, true).err().unwrap();
+        assert!(error.contains("Another rakke process"), "{}", error);
+        assert!(dir.join("file.lock").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
\ No newline at end of file
@@ -1,43 +1,131 @@
 use std::env;
+use std::ffi::OsString;
 
+// Commands parse arguments and report errors; the repository logic lives in the library
 mod init;
 mod add;
+mod commit;
+mod checkout;
+mod commit_tree;
+mod mktree;
+mod mktag;
+mod interpret_trailers;
+mod stripspace;
+mod selftest;
+mod diagnose;
+mod cat_file;
+mod hash_object;
+mod status;
 
 fn main() {
-    // Get command line arguments
-    let args: Vec<String> = env::args().collect();
+    // Get command line arguments as given; commands that take file names receive them raw,
+    // so a non-UTF-8 name can be passed straight to add or hash-object
+    let args: Vec<OsString> = env::args_os().collect();
     
     // Check if we have at least one command
     if args.len() < 2 {
         eprintln!("Usage: rakke <command>");
-        eprintln!("Available commands: init, add, --version");
+        eprintln!("Available commands: init, add, status, commit, checkout, commit-tree, mktree, mktag, cat-file, hash-object, interpret-trailers, stripspace, diagnose, --version");
         return;
     }
     
     // Get first command after program name
-    let command = &args[1];
+    let command = args[1].to_string_lossy();
     
     // Command dispatcher
-    match command.as_str() {
+    match command.as_ref() {
         "init" => {
             // Pass arguments to init module for complete isolation
-            let init_args: Vec<String> = args[1..].to_vec();
+            let init_args: Vec<String> = utf8_args(&args[1..]);
             init::execute(init_args);
         }
         "add" => {
             // Pass arguments to init module for complete isolation
-            let init_args: Vec<String> = args[1..].to_vec();
+            let init_args: Vec<OsString> = args[1..].to_vec();
             add::execute(init_args);
         }
+        "status" => {
+            // Pass arguments to status module for complete isolation
+            let status_args: Vec<String> = utf8_args(&args[1..]);
+            status::execute(status_args);
+        }
+        "commit" => {
+            // Pass arguments to commit module for complete isolation
+            let commit_args: Vec<String> = utf8_args(&args[1..]);
+            commit::execute(commit_args);
+        }
+        "checkout" => {
+            // Pass arguments to checkout module for complete isolation
+            let checkout_args: Vec<String> = utf8_args(&args[1..]);
+            checkout::execute(checkout_args);
+        }
+        "commit-tree" => {
+            // Pass arguments to commit-tree module for complete isolation
+            let commit_tree_args: Vec<String> = utf8_args(&args[1..]);
+            commit_tree::execute(commit_tree_args);
+        }
+        "mktree" => {
+            // Pass arguments to mktree module for complete isolation
+            let mktree_args: Vec<String> = utf8_args(&args[1..]);
+            mktree::execute(mktree_args);
+        }
+        "mktag" => {
+            // Pass arguments to mktag module for complete isolation
+            let mktag_args: Vec<String> = utf8_args(&args[1..]);
+            mktag::execute(mktag_args);
+        }
+        "cat-file" => {
+            // Pass arguments to cat-file module for complete isolation
+            let cat_file_args: Vec<String> = utf8_args(&args[1..]);
+            cat_file::execute(cat_file_args);
+        }
+        "hash-object" => {
+            // Pass arguments to hash-object module for complete isolation
+            let hash_object_args: Vec<OsString> = args[1..].to_vec();
+            hash_object::execute(hash_object_args);
+        }
+        "interpret-trailers" => {
+            // Pass arguments to interpret-trailers module for complete isolation
+            let trailer_args: Vec<String> = utf8_args(&args[1..]);
+            interpret_trailers::execute(trailer_args);
+        }
+        "stripspace" => {
+            // Pass arguments to stripspace module for complete isolation
+            let stripspace_args: Vec<String> = utf8_args(&args[1..]);
+            stripspace::execute(stripspace_args);
+        }
+        "diagnose" => {
+            // Pass arguments to diagnose module for complete isolation
+            let diagnose_args: Vec<String> = utf8_args(&args[1..]);
+            diagnose::execute(diagnose_args);
+        }
+        "selftest" => {
+            // Diagnostic command, deliberately left out of the command list
+            let selftest_args: Vec<String> = utf8_args(&args[1..]);
+            selftest::execute(selftest_args);
+        }
         "--version" | "-v" => {
             // Show version information
             println!("rakke version {}", env!("CARGO_PKG_VERSION"));
-
+        
         }
         _ => {
             // Unknown command
             eprintln!("Unknown command: {}", command);
-            eprintln!("Available commands: init, --version");
+            eprintln!("Available commands: init, add, status, commit, checkout, commit-tree, mktree, mktag, cat-file, hash-object, interpret-trailers, stripspace, diagnose, --version");
         }
     }
+}
+
+// Arguments for commands that only take text; file names are the exception, not the rule
+fn utf8_args(args: &[OsString]) -> Vec<String> {
+    args.iter()
+        .map(|arg| match arg.to_str() {
+            Some(arg) => arg.to_string(),
+            None => {
+                eprintln!("fatal: argument is not valid UTF-8: {}", arg.to_string_lossy());
+                std::process::exit(128);
+            }
+        })
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,157 @@
+use crate::config::Config;
+
+// Read core.commentChar, the character that starts comment lines in edited messages
+pub fn comment_char(config: &Config) -> Result<char, String> {
+    let value = match config.get("core.commentchar") {
+        Some(value) => value,
+        None => return Ok('#'),
+    };
+    
+    // "auto" only changes the character in commit templates; cleaning still uses '#'
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok('#');
+    }
+    
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err("core.commentChar should only be one character".to_string()),
+    }
+}
+
+// Normalize a message like git stripspace: drop trailing whitespace, collapse runs of
+// blank lines, remove leading and trailing blank lines and end with a newline.
+// With a comment character, lines starting with it are removed as well.
+pub fn stripspace(text: &str, comment: Option<char>) -> String {
+    let mut output = String::new();
+    let mut empties = 0;
+    
+    for line in text.split_inclusive('\n') {
+        // Comment lines vanish without counting as blank lines
+        if let Some(c) = comment {
+            if line.starts_with(c) {
+                continue;
+            }
+        }
+        
+        // Trailing whitespace includes the '\r' of CRLF line endings
+        let line = line.trim_end_matches([' ', '\t', '\n', '\r']);
+        if line.is_empty() {
+            empties += 1;
+            continue;
+        }
+        
+        // A run of blank lines between content becomes a single one
+        if empties > 0 && !output.is_empty() {
+            output.push('\n');
+        }
+        empties = 0;
+        
+        output.push_str(line);
+        output.push('\n');
+    }
+    
+    output
+}
+
+// Prefix every line with the comment character, as git does for editor templates
+pub fn comment_lines(text: &str, comment: char) -> String {
+    let mut output = String::new();
+    
+    for line in text.split_inclusive('\n') {
+        // Empty and tab-indented lines get no space after the comment character
+        output.push(comment);
+        if !line.starts_with('\n') && !line.starts_with('\t') {
+            output.push(' ');
+        }
+        output.push_str(line);
+    }
+    
+    if !output.is_empty() && !output.ends_with('\n') {
+        output.push('\n');
+    }
+    
+    output
+}
+
+// How a commit message is cleaned up before it is stored (git's --cleanup modes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cleanup {
+    Verbatim,   // Store the message exactly as given
+    Whitespace, // Normalize whitespace but keep comment lines
+    Strip,      // Normalize whitespace and remove comment lines
+}
+
+// Parse a --cleanup or commit.cleanup value; "default" depends on whether an editor was used
+pub fn parse_cleanup(value: &str, editor_used: bool) -> Option<Cleanup> {
+    match value {
+        "verbatim" => Some(Cleanup::Verbatim),
+        "whitespace" => Some(Cleanup::Whitespace),
+        "strip" => Some(Cleanup::Strip),
+        "default" if editor_used => Some(Cleanup::Strip),
+        "default" => Some(Cleanup::Whitespace),
+        _ => None,
+    }
+}
+
+// Clean a commit message the way git commit does, so the same text gives the same commit hash.
+// Apart from verbatim mode, trailing whitespace (including CR of CRLF) and blank lines at either
+// end are removed, inner runs of blank lines collapse to one and the message ends in one newline.
+pub fn clean_commit_message(message: &str, cleanup: Cleanup, comment: char) -> String {
+    match cleanup {
+        Cleanup::Verbatim => message.to_string(),
+        Cleanup::Whitespace => stripspace(message, None),
+        Cleanup::Strip => stripspace(message, Some(comment)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Expected outputs below are what git stripspace prints for the same input
+    #[test]
+    fn crlf_input_is_normalized() {
+        assert_eq!(stripspace("a\r\nb  \r\n\r\n\r\nc\r\n", None), "a\nb\n\nc\n");
+        assert_eq!(stripspace("# a\r\nb\r\n", Some('#')), "b\n");
+        assert_eq!(comment_lines("a\r\n\r\n\tb\nc", '#'), "# a\r\n# \r\n#\tb\n# c\n");
+    }
+    
+    #[test]
+    fn comment_only_input() {
+        assert_eq!(stripspace("# one\n# two\n", Some('#')), "");
+        assert_eq!(stripspace("# one\n\n# two\n", None), "# one\n\n# two\n");
+        assert_eq!(stripspace("\n\n  \n", None), "");
+        assert_eq!(stripspace("", Some('#')), "");
+    }
+    
+    #[test]
+    fn comment_char_at_start_of_content() {
+        // Only lines that begin with the comment character are comments
+        assert_eq!(stripspace("#include <x>\nissue #1\n\n\n", Some('#')), "issue #1\n");
+        assert_eq!(stripspace("#include <x>\nissue #1\n", Some(';')), "#include <x>\nissue #1\n");
+        assert_eq!(stripspace(" # indented\n", Some('#')), " # indented\n");
+        assert_eq!(stripspace("; note\n#tag\n", Some(';')), "#tag\n");
+    }
+    
+    #[test]
+    fn commit_message_cleanup_modes() {
+        let message = "\n\nSubject  \r\n\r\n# comment\r\nBody\r\n\r\n\r\n";
+        assert_eq!(clean_commit_message(message, Cleanup::Strip, '#'), "Subject\n\nBody\n");
+        assert_eq!(clean_commit_message(message, Cleanup::Whitespace, '#'), "Subject\n\n# comment\nBody\n");
+        assert_eq!(clean_commit_message(message, Cleanup::Verbatim, '#'), message);
+        
+        // A message without a final newline gets one, and a comment-only message becomes empty
+        assert_eq!(clean_commit_message("Subject", Cleanup::Whitespace, '#'), "Subject\n");
+        assert_eq!(clean_commit_message("# only\n", Cleanup::Strip, '#'), "");
+        assert_eq!(clean_commit_message("; kept\n# gone?\n", Cleanup::Strip, ';'), "# gone?\n");
+    }
+    
+    #[test]
+    fn cleanup_mode_names() {
+        assert_eq!(parse_cleanup("default", false), Some(Cleanup::Whitespace));
+        assert_eq!(parse_cleanup("default", true), Some(Cleanup::Strip));
+        assert_eq!(parse_cleanup("verbatim", false), Some(Cleanup::Verbatim));
+        assert_eq!(parse_cleanup("scissors", false), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,53 @@
+use std::io::{self, Read};
+use rakke::objects;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<String>) {
+    // mktag takes no arguments besides help (skip "mktag" command itself)
+    if let Some(arg) = args.get(1) {
+        if arg == "--help" || arg == "-h" {
+            print_help();
+            return;
+        }
+        
+        eprintln!("Unknown option: {}", arg);
+        print_help();
+        std::process::exit(129);
+    }
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    let mut content = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut content) {
+        eprintln!("fatal: Cannot read from stdin: {}", e);
+        std::process::exit(128);
+    }
+    
+    // Reject anything git's strict fsck would not accept
+    if let Err(e) = objects::check_tag(&content) {
+        eprintln!("error: tag input does not pass fsck: {}", e);
+        eprintln!("fatal: tag on stdin did not pass our strict fsck check");
+        std::process::exit(128);
+    }
+    
+    match objects::write_tag(&repo, &content) {
+        Ok(hash) => println!("{}", hash),
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+fn print_help() {
+    println!("usage: rakke mktag < <tag-content>");
+    println!();
+    println!("    -h, --help            show help");
+}
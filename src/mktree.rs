@@ -0,0 +1,56 @@
+use std::io::{self, Read};
+use rakke::objects;
+use rakke::repository::Repository;
+
+pub fn execute(args: Vec<String>) {
+    let mut nul_terminated = false;
+    let mut allow_missing = false;
+    
+    // Process arguments (skip "mktree" command itself)
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "-z" => nul_terminated = true,
+            "--missing" => allow_missing = true,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            _ => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+    }
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    let mut input = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut input) {
+        eprintln!("fatal: Cannot read from stdin: {}", e);
+        std::process::exit(128);
+    }
+    
+    match objects::make_tree(&repo, &input, nul_terminated, allow_missing) {
+        Ok(hash) => println!("{}", hash),
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+fn print_help() {
+    println!("usage: rakke mktree [-z] [--missing]");
+    println!();
+    println!("    -z                    input is NUL terminated");
+    println!("    --missing             allow missing objects");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
@@ -0,0 +1,808 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use flate2::{Compression, Decompress, FlushDecompress, Status};
+use flate2::write::ZlibEncoder;
+use crate::config::Config;
+use crate::hash::Sha1;
+use crate::hex;
+use crate::ident::{self, Ident, Role};
+use crate::paths;
+use crate::refs;
+use crate::repository::Repository;
+
+// Longest possible loose object header: "commit " plus a 20-digit size and the NUL
+const MAX_HEADER_LEN: usize = 28;
+
+// Distinguishes the temporary files of objects written by different threads of one process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Write a loose object of the given type ("blob", "tree", "commit" or "tag") and return its hash
+pub fn write_object(repo: &Repository, kind: &str, content: &[u8]) -> Result<String, String> {
+    // The header and content are hashed and compressed in turn, so the content is never
+    // copied into a combined buffer
+    let header = object_header(kind, content.len());
+    let hash = hash_object(kind, content);
+    
+    // Create object file path: .git/objects/xx/yyyyyyy...
+    let object_path = object_path(repo, &hash);
+    let object_dir = repo.git_path(&format!("objects/{}", &hash[..2]));
+    
+    // Objects are renamed into place whole, so one that is already there is left alone
+    if object_path.exists() {
+        return Ok(hash);
+    }
+    
+    // Compress object content using zlib
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(header.as_bytes())
+        .and_then(|_| encoder.write_all(content))
+        .map_err(|e| format!("Compression error: {}", e))?;
+    let compressed = encoder.finish()
+        .map_err(|e| format!("Compression finish error: {}", e))?;
+    
+    // Create object directory if it doesn't exist
+    if !object_dir.exists() {
+        fs::create_dir_all(&object_dir)
+            .map_err(|e| format!("Cannot create object directory: {}", e))?;
+    }
+    
+    // Write to a temporary file beside the object and rename it into place, so readers never
+    // see a partial object and concurrent writers of the same object both succeed
+    let temp_path = object_dir.join(format!(
+        "tmp_obj_{}_{}",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| file.write_all(&compressed))
+        .and_then(|_| fs::rename(&temp_path, &object_path));
+    
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Cannot write object file: {}", e));
+    }
+    
+    Ok(hash)
+}
+
+// Compute the hash an object would be stored under, without writing it
+pub fn hash_object(kind: &str, content: &[u8]) -> String {
+    let header = object_header(kind, content.len());
+    let mut hasher = Sha1::new();
+    hasher.update(header.as_bytes()).update(content);
+    hasher.finalize_hex()
+}
+
+// Git object format is "<type> <size>\0<content>"
+fn object_header(kind: &str, size: usize) -> String {
+    format!("{} {}\0", kind, size)
+}
+
+// Read a loose object, returning its type and content
+pub fn read_object(repo: &Repository, hash: &str) -> Result<(String, Vec<u8>), String> {
+    if !is_valid_hash(hash) {
+        return Err(format!("Not a valid object name {}", hash));
+    }
+    
+    let path = object_path(repo, hash);
+    if !path.exists() {
+        return Err(format!("Not a valid object name {}", hash));
+    }
+    
+    // Read and decompress the object file
+    let compressed = fs::read(&path)
+        .map_err(|e| format!("Cannot read object {}: {}", hash, e))?;
+    
+    inflate_object(&compressed, repo.max_object_size()).map_err(|e| match e {
+        InflateError::Corrupt(e) => format!("Corrupt object {}: {}", hash, e),
+        InflateError::TooLarge(kind, size) => format!(
+            "{} {} is {} bytes, over the limit of {} bytes set by rakke.maxObjectSize",
+            kind, hash, size, repo.max_object_size()
+        ),
+    })
+}
+
+// Why a loose object could not be inflated
+#[derive(Debug, PartialEq)]
+enum InflateError {
+    Corrupt(String),
+    TooLarge(String, u64), // Type and declared size of an object over the size limit
+}
+
+impl From<String> for InflateError {
+    fn from(e: String) -> Self {
+        InflateError::Corrupt(e)
+    }
+}
+
+impl From<&str> for InflateError {
+    fn from(e: &str) -> Self {
+        InflateError::Corrupt(e.to_string())
+    }
+}
+
+// Inflate a loose object, never producing more than its header declares or max_size allows
+fn inflate_object(compressed: &[u8], max_size: u64) -> Result<(String, Vec<u8>), InflateError> {
+    let mut decoder = Decompress::new(true);
+    let mut data = Vec::new();
+    
+    // Inflate only enough to read the "<type> <size>\0" header
+    let mut ended = inflate_limited(&mut decoder, compressed, &mut data, MAX_HEADER_LEN)?;
+    let nul = data.iter().position(|&b| b == 0)
+        .ok_or("missing header terminator")?;
+    let (kind, size) = parse_header(&data[..nul])?;
+    
+    // A size beyond the ceiling is refused before any of the content is inflated
+    if size > max_size {
+        return Err(InflateError::TooLarge(kind, size));
+    }
+    let size = usize::try_from(size).map_err(|_| "invalid header")?;
+    
+    // Inflate the rest, allowing one byte past the declared size to detect overlong content
+    let expected = (nul + 1).checked_add(size).ok_or("invalid header")?;
+    if !ended {
+        ended = inflate_limited(&mut decoder, compressed, &mut data, expected.saturating_add(1))?;
+    }
+    if !ended || data.len() != expected {
+        return Err("size mismatch".into());
+    }
+    
+    // Anything after the zlib stream is not part of the object
+    if decoder.total_in() as usize != compressed.len() {
+        return Err("garbage at end of loose object".into());
+    }
+    
+    let content = data.split_off(nul + 1);
+    Ok((kind, content))
+}
+
+// Parse a loose object header without its NUL: "<type> <size>"
+fn parse_header(header: &[u8]) -> Result<(String, u64), String> {
+    let header = std::str::from_utf8(header)
+        .map_err(|_| "invalid header")?;
+    let (kind, size) = header.split_once(' ')
+        .ok_or("invalid header")?;
+    
+    if !matches!(kind, "blob" | "tree" | "commit" | "tag") {
+        return Err(format!("invalid object type '{}'", kind));
+    }
+    let size = size.parse::<u64>()
+        .map_err(|_| "invalid header")?;
+    Ok((kind.to_string(), size))
+}
+
+// Read only the type and size of a loose object, inflating no more than its header
+pub fn read_object_header(repo: &Repository, hash: &str) -> Result<(String, u64), String> {
+    let (_, kind, size, _) = open_object(repo, hash)?;
+    Ok((kind, size))
+}
+
+// Copy the content of a loose object to out as it is inflated, so that objects of any size,
+// including those over rakke.maxObjectSize, can be shown. Returns the object's type.
+pub fn stream_object<W: Write>(repo: &Repository, hash: &str, out: &mut W) -> io::Result<String> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let corrupt = |e: String| invalid(format!("Corrupt object {}: {}", hash, e));
+    let (mut reader, kind, size, start) = open_object(repo, hash).map_err(invalid)?;
+    
+    // Content inflated along with the header comes first
+    let mut written = start.len() as u64;
+    if written > size {
+        return Err(corrupt("size mismatch".to_string()));
+    }
+    out.write_all(&start)?;
+    
+    let mut buffer = vec![0u8; 64 << 10];
+    loop {
+        let read = reader.inflate(&mut buffer).map_err(corrupt)?;
+        if read == 0 {
+            break;
+        }
+        written += read as u64;
+        if written > size {
+            return Err(corrupt("size mismatch".to_string()));
+        }
+        out.write_all(&buffer[..read])?;
+    }
+    if written != size {
+        return Err(corrupt("size mismatch".to_string()));
+    }
+    
+    // Anything after the zlib stream is not part of the object
+    if !reader.file.fill_buf()?.is_empty() {
+        return Err(corrupt("garbage at end of loose object".to_string()));
+    }
+    Ok(kind)
+}
+
+// Open a loose object and inflate its header, returning the reader positioned after it, the
+// type and size, and whatever content was inflated along with the header
+fn open_object(repo: &Repository, hash: &str) -> Result<(ObjectReader, String, u64, Vec<u8>), String> {
+    if !is_valid_hash(hash) {
+        return Err(format!("Not a valid object name {}", hash));
+    }
+    let file = File::open(object_path(repo, hash))
+        .map_err(|_| format!("Not a valid object name {}", hash))?;
+    let mut reader = ObjectReader { file: BufReader::new(file), decoder: Decompress::new(true), ended: false };
+    
+    let mut data = [0u8; MAX_HEADER_LEN];
+    let mut filled = 0;
+    let nul = loop {
+        if let Some(nul) = data[..filled].iter().position(|&b| b == 0) {
+            break nul;
+        }
+        let read = match filled {
+            MAX_HEADER_LEN => 0,
+            _ => reader.inflate(&mut data[filled..]).map_err(|e| format!("Corrupt object {}: {}", hash, e))?,
+        };
+        if read == 0 {
+            return Err(format!("Corrupt object {}: missing header terminator", hash));
+        }
+        filled += read;
+    };
+    
+    let (kind, size) = parse_header(&data[..nul])
+        .map_err(|e| format!("Corrupt object {}: {}", hash, e))?;
+    Ok((reader, kind, size, data[nul + 1..filled].to_vec()))
+}
+
+// Inflates a loose object file a piece at a time
+struct ObjectReader {
+    file: BufReader<File>,
+    decoder: Decompress,
+    ended: bool, // The end of the zlib stream was reached and its checksum verified
+}
+
+impl ObjectReader {
+    // Inflate into out, returning how many bytes were produced; 0 only at the end of the stream
+    fn inflate(&mut self, out: &mut [u8]) -> Result<usize, String> {
+        while !self.ended {
+            let input = self.file.fill_buf()
+                .map_err(|e| format!("Cannot read object file: {}", e))?;
+            let (in_before, out_before) = (self.decoder.total_in(), self.decoder.total_out());
+            let status = self.decoder.decompress(input, out, FlushDecompress::None)
+                .map_err(|e| format!("Decompression error: {}", e))?;
+            let consumed = (self.decoder.total_in() - in_before) as usize;
+            let produced = (self.decoder.total_out() - out_before) as usize;
+            self.file.consume(consumed);
+            self.ended = status == Status::StreamEnd;
+            
+            if produced > 0 {
+                return Ok(produced);
+            }
+            
+            // Running out of input without reaching the end means the checksum was never verified
+            if consumed == 0 && !self.ended {
+                return Err("Decompression error: truncated zlib stream".to_string());
+            }
+        }
+        Ok(0)
+    }
+}
+
+// Hash a file as an object of the given kind, also storing it in repo when one is given
+pub fn hash_file(path: &Path, kind: &str, repo: Option<&Repository>) -> Result<String, String> {
+    let content = fs::read(path)
+        .map_err(|e| format!("could not open '{}' for reading: {}", path.display(), e))?;
+    
+    hash_content(kind, &content, repo)
+}
+
+// Hash content as an object of the given kind, refusing content other tools could not read back
+pub fn hash_content(kind: &str, content: &[u8], repo: Option<&Repository>) -> Result<String, String> {
+    check_format(kind, content)?;
+    
+    match repo {
+        Some(repo) => write_object(repo, kind, content),
+        None => Ok(hash_object(kind, content)),
+    }
+}
+
+// Check that content is well-formed for its object type; blobs can hold anything
+fn check_format(kind: &str, content: &[u8]) -> Result<(), String> {
+    let valid = match kind {
+        "tree" => parse_tree(content).is_ok(),
+        "commit" => content.starts_with(b"tree ")
+            && content.len() > 45
+            && content[45] == b'\n'
+            && std::str::from_utf8(&content[5..45]).is_ok_and(is_valid_hash),
+        "tag" => check_tag(content).is_ok(),
+        _ => true,
+    };
+    
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("corrupt {}", kind))
+    }
+}
+
+// Write a commit object for an existing tree and parents without touching any ref
+pub fn write_commit(repo: &Repository, tree: &str, parents: &[String], message: &str) -> Result<String, String> {
+    // The tree and every parent must exist and have the right type
+    expect_object_type(repo, tree, "tree")?;
+    for parent in parents {
+        expect_object_type(repo, parent, "commit")?;
+    }
+    
+    let config = Config::load(repo)?;
+    let author = ident::resolve(Role::Author, &config)?;
+    let committer = ident::resolve(Role::Committer, &config)?;
+    
+    let content = commit_content(tree, parents, &author, &committer, message);
+    write_object(repo, "commit", &content)
+}
+
+fn expect_object_type(repo: &Repository, hash: &str, expected: &str) -> Result<(), String> {
+    let (kind, _) = read_object_header(repo, hash)?;
+    
+    if kind != expected {
+        return Err(format!("{} is a {}, not a {}", hash, kind, expected));
+    }
+    
+    Ok(())
+}
+
+// The tree a commit points to, from its first header line
+pub fn commit_tree_hash(repo: &Repository, commit: &str) -> Result<String, String> {
+    let (kind, content) = read_object(repo, commit)?;
+    if kind != "commit" {
+        return Err(format!("{} is a {}, not a commit", commit, kind));
+    }
+    
+    String::from_utf8_lossy(&content)
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("tree "))
+        .map(|tree| tree.to_string())
+        .ok_or_else(|| format!("corrupt commit {}", commit))
+}
+
+// Write a validated tag object after confirming the tagged object exists with the declared type
+pub fn write_tag(repo: &Repository, content: &[u8]) -> Result<String, String> {
+    let (object, declared_type) = check_tag(content)?;
+    
+    let (actual_type, _) = read_object_header(repo, &object)
+        .map_err(|_| format!("could not read tagged object '{}'", object))?;
+    
+    if actual_type != declared_type {
+        return Err(format!("object '{}' tagged as '{}', but is a '{}' type", object, declared_type, actual_type));
+    }
+    
+    write_object(repo, "tag", content)
+}
+
+// Build the content of a commit object
+pub fn commit_content(tree: &str, parents: &[String], author: &Ident, committer: &Ident, message: &str) -> Vec<u8> {
+    let mut content = format!("tree {}\n", tree);
+    
+    for parent in parents {
+        content.push_str(&format!("parent {}\n", parent));
+    }
+    
+    content.push_str(&format!("author {}\n", author.to_header()));
+    content.push_str(&format!("committer {}\n", committer.to_header()));
+    
+    // A blank line separates the headers from the message
+    content.push('\n');
+    content.push_str(message);
+    
+    content.into_bytes()
+}
+
+// One entry of a tree object
+#[derive(Debug, Clone)]
+pub struct TreeEntry {
+    pub mode: u32,     // 0o100644, 0o100755, 0o120000, 0o040000 or 0o160000
+    pub name: Vec<u8>, // Single path component, as raw bytes
+    pub hash: String,  // Object the entry points to
+}
+
+// Object type a tree entry mode refers to
+pub fn mode_object_type(mode: u32) -> &'static str {
+    match mode {
+        0o040000 => "tree",
+        0o160000 => "commit", // Gitlink to a submodule commit
+        _ => "blob",
+    }
+}
+
+// Split tree object content into its entries, in stored order
+pub fn parse_tree(content: &[u8]) -> Result<Vec<TreeEntry>, String> {
+    let mut entries = Vec::new();
+    let mut rest = content;
+    
+    // Each entry is "<mode in octal> <name>\0<20-byte hash>"
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')
+            .ok_or("malformed tree entry: missing mode")?;
+        let nul = rest.iter().position(|&b| b == 0)
+            .filter(|&nul| nul > space)
+            .ok_or("malformed tree entry: missing name terminator")?;
+        if rest.len() < nul + 21 {
+            return Err("malformed tree entry: truncated hash".to_string());
+        }
+        
+        let mode = std::str::from_utf8(&rest[..space]).ok()
+            .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+            .ok_or("malformed tree entry: bad mode")?;
+        entries.push(TreeEntry {
+            mode,
+            name: rest[space + 1..nul].to_vec(),
+            hash: hex::encode(&rest[nul + 1..nul + 21]),
+        });
+        rest = &rest[nul + 21..];
+    }
+    
+    Ok(entries)
+}
+
+// Build the content of a tree object, sorting entries the way git does
+pub fn tree_content(entries: &mut [TreeEntry]) -> Result<Vec<u8>, String> {
+    // Subtrees sort as if their name ended with '/'
+    let sort_key = |entry: &TreeEntry| {
+        let mut key = entry.name.clone();
+        if entry.mode == 0o040000 {
+            key.push(b'/');
+        }
+        key
+    };
+    entries.sort_by_key(sort_key);
+    
+    let mut content = Vec::new();
+    for entry in entries.iter() {
+        // "<mode in octal> <name>\0<20-byte hash>"
+        content.extend_from_slice(format!("{:o} ", entry.mode).as_bytes());
+        content.extend_from_slice(&entry.name);
+        content.push(0);
+        let hash_bytes = hex::decode(&entry.hash)?;
+        if hash_bytes.len() != 20 {
+            return Err(format!("Invalid SHA-1 hash length for '{}'", String::from_utf8_lossy(&entry.name)));
+        }
+        content.extend_from_slice(&hash_bytes);
+    }
+    
+    Ok(content)
+}
+
+// Build a tree object from "mode type hash\tname" records, as printed by ls-tree
+pub fn make_tree(repo: &Repository, input: &[u8], nul_terminated: bool, allow_missing: bool) -> Result<String, String> {
+    let terminator = if nul_terminated { 0 } else { b'\n' };
+    let mut entries = Vec::new();
+    
+    for record in input.split(|&b| b == terminator) {
+        if record.is_empty() {
+            continue;
+        }
+        entries.push(parse_record(repo, record, nul_terminated, allow_missing)?);
+    }
+    
+    let content = tree_content(&mut entries)?;
+    write_object(repo, "tree", &content)
+}
+
+fn parse_record(repo: &Repository, record: &[u8], nul_terminated: bool, allow_missing: bool) -> Result<TreeEntry, String> {
+    let malformed = || format!("input format error: {}", String::from_utf8_lossy(record));
+    
+    // The name is raw bytes; everything before it must be text
+    let tab = record.iter().position(|&b| b == b'\t').ok_or_else(malformed)?;
+    let info = std::str::from_utf8(&record[..tab]).map_err(|_| malformed())?;
+    let mut name = record[tab + 1..].to_vec();
+    
+    // Without -z, names with unusual characters arrive C-style quoted, as ls-tree prints them
+    if !nul_terminated && name.starts_with(b"\"") {
+        name = paths::unquote_c_style(&name).ok_or_else(malformed)?;
+    }
+    let display_name = String::from_utf8_lossy(&name).into_owned();
+    
+    let mut fields = info.split(' ');
+    let (mode, kind, hash) = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+        (Some(mode), Some(kind), Some(hash), None) => (mode, kind, hash),
+        _ => return Err(malformed()),
+    };
+    
+    let mode = u32::from_str_radix(mode, 8).map_err(|_| malformed())?;
+    
+    if name.is_empty() || name.contains(&b'/') {
+        return Err(format!("path {} contains slash", display_name));
+    }
+    
+    if !is_valid_hash(hash) {
+        return Err(malformed());
+    }
+    
+    // The type written on the line must agree with the mode
+    let mode_kind = mode_object_type(mode);
+    if kind != mode_kind {
+        return Err(format!("entry '{}' object type ({}) doesn't match mode type ({})", display_name, kind, mode_kind));
+    }
+    
+    // Gitlinks usually point into another repository, so only a missing submodule commit is fine
+    match read_object_header(repo, hash) {
+        Ok((actual_kind, _)) if actual_kind != kind => {
+            return Err(format!("entry '{}' object {} is a {} but specified type was ({})", display_name, hash, actual_kind, kind));
+        }
+        Ok(_) => {}
+        Err(_) if allow_missing || mode_kind == "commit" => {}
+        Err(_) => return Err(format!("entry '{}' object {} is unavailable", display_name, hash)),
+    }
+    
+    Ok(TreeEntry {
+        mode,
+        name,
+        hash: hash.to_string(),
+    })
+}
+
+// Strictly validate an annotated tag object, as git's fsck does, returning the tagged object and type.
+// Errors are "<fsck message id>: <description>".
+pub fn check_tag(content: &[u8]) -> Result<(String, String), String> {
+    let text = String::from_utf8_lossy(content);
+    
+    // Headers end at the first blank line, or at the final newline of a message-less tag
+    let headers_end = match text.find("\n\n") {
+        Some(pos) => pos + 1,
+        None if text.ends_with('\n') => text.len(),
+        None => return Err("unterminatedHeader: unterminated header".to_string()),
+    };
+    let headers = &text[..headers_end];
+    
+    if headers.contains('\0') {
+        return Err("nulInHeader: unterminated header: NUL at offset".to_string());
+    }
+    
+    let rest = headers.strip_prefix("object ")
+        .ok_or("missingObject: invalid format - expected 'object' line")?;
+    let (object, rest) = rest.split_once('\n')
+        .filter(|(hash, _)| is_valid_hash(hash))
+        .ok_or("badObjectSha1: invalid 'object' line format - bad sha1")?;
+    
+    let rest = rest.strip_prefix("type ")
+        .ok_or("missingTypeEntry: invalid format - expected 'type' line")?;
+    let (kind, rest) = rest.split_once('\n')
+        .ok_or("missingType: invalid format - unexpected end after 'type' line")?;
+    if !matches!(kind, "blob" | "tree" | "commit" | "tag") {
+        return Err("badType: invalid 'type' value".to_string());
+    }
+    
+    let rest = rest.strip_prefix("tag ")
+        .ok_or("missingTagEntry: invalid format - expected 'tag' line")?;
+    let (name, rest) = rest.split_once('\n')
+        .ok_or("missingTag: invalid format - unexpected end after 'type' line")?;
+    if !refs::is_valid_name(&format!("refs/tags/{}", name)) {
+        return Err(format!("badTagName: invalid 'tag' name: {}", name));
+    }
+    
+    let rest = rest.strip_prefix("tagger ")
+        .ok_or("missingTaggerEntry: invalid format - expected 'tagger' line")?;
+    let (tagger, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+    check_ident(tagger)?;
+    
+    // Nothing may follow the tagger header except the message
+    if !rest.is_empty() {
+        return Err("extraHeaderEntry: invalid format - extra header(s) after 'tagger'".to_string());
+    }
+    
+    Ok((object.to_string(), kind.to_string()))
+}
+
+// Validate an "author", "committer" or "tagger" value: "Name <email> <seconds> <+hhmm>"
+pub fn check_ident(ident: &str) -> Result<(), String> {
+    if ident.starts_with('<') {
+        return Err("missingNameBeforeEmail: invalid author/committer line - missing space before email".to_string());
+    }
+    
+    // The name runs up to the opening bracket of the email
+    let name_end = ident.find(['<', '>']).unwrap_or(ident.len());
+    match ident[name_end..].chars().next() {
+        Some('<') => {}
+        Some('>') => return Err("badName: invalid author/committer line - bad name".to_string()),
+        _ => return Err("missingEmail: invalid author/committer line - missing email".to_string()),
+    }
+    if !ident[..name_end].ends_with(' ') {
+        return Err("missingSpaceBeforeEmail: invalid author/committer line - missing space before email".to_string());
+    }
+    
+    let after_name = &ident[name_end + 1..];
+    let email_end = after_name.find(['<', '>']).unwrap_or(after_name.len());
+    if !after_name[email_end..].starts_with('>') {
+        return Err("badEmail: invalid author/committer line - bad email".to_string());
+    }
+    
+    let date = after_name[email_end + 1..].strip_prefix(' ')
+        .ok_or("missingSpaceBeforeDate: invalid author/committer line - missing space before date")?;
+    
+    let digits = date.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if date.starts_with('0') && digits > 1 {
+        return Err("zeroPaddedDate: invalid author/committer line - zero-padded date".to_string());
+    }
+    if digits > 0 && date[..digits].parse::<i64>().is_err() {
+        return Err("badDateOverflow: invalid author/committer line - date causes integer overflow".to_string());
+    }
+    
+    let zone = date[digits..].strip_prefix(' ')
+        .filter(|_| digits > 0)
+        .ok_or("badDate: invalid author/committer line - bad date")?;
+    
+    let valid_zone = zone.len() == 5
+        && (zone.starts_with('+') || zone.starts_with('-'))
+        && zone[1..].bytes().all(|b| b.is_ascii_digit());
+    if !valid_zone {
+        return Err("badTimezone: invalid author/committer line - bad time zone".to_string());
+    }
+    
+    Ok(())
+}
+
+// Full object names are 40 lowercase hex digits
+pub fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+// Expand an abbreviated object id (at least 4 hex digits) to the single loose object it names
+pub fn find_object(repo: &Repository, name: &str) -> Result<String, String> {
+    let prefix = name.to_lowercase();
+    if prefix.len() < 4 || prefix.len() > 40 || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Not a valid object name {}", name));
+    }
+    
+    // Objects are spread over directories named by their first two digits
+    let dir = repo.git_path(&format!("objects/{}", &prefix[..2]));
+    let mut matches: Vec<String> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| format!("{}{}", &prefix[..2], entry.file_name().to_string_lossy()))
+            .filter(|hash| is_valid_hash(hash) && hash.starts_with(&prefix))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    
+    match matches.len() {
+        0 => Err(format!("Not a valid object name {}", name)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!("short object ID {} is ambiguous", name)),
+    }
+}
+
+// Location of a loose object: .git/objects/xx/yyyyyyy...
+fn object_path(repo: &Repository, hash: &str) -> PathBuf {
+    repo.git_path(&format!("objects/{}/{}", &hash[..2], &hash[2..]))
+}
+
+// Compress data using zlib compression
+pub fn compress_zlib(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)
+        .map_err(|e| format!("Compression error: {}", e))?;
+    
+    encoder.finish()
+        .map_err(|e| format!("Compression finish error: {}", e))
+}
+
+// Decompress a complete zlib stream of any size
+pub fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoder = Decompress::new(true);
+    let mut output = Vec::new();
+    
+    // Without a limit this only returns once the stream has ended
+    inflate_limited(&mut decoder, data, &mut output, usize::MAX)?;
+    Ok(output)
+}
+
+// Inflate into output until the zlib stream ends (true) or output holds limit bytes (false).
+// The buffer grows with the data actually inflated, so a bogus size cannot force a huge allocation.
+fn inflate_limited(decoder: &mut Decompress, data: &[u8], output: &mut Vec<u8>, limit: usize) -> Result<bool, String> {
+    while output.len() < limit {
+        if output.len() == output.capacity() {
+            let room = (limit - output.len()).min(output.len().max(8192));
+            output.reserve_exact(room);
+        }
+        
+        let (in_before, out_before) = (decoder.total_in(), decoder.total_out());
+        let status = decoder.decompress_vec(&data[in_before as usize..], output, FlushDecompress::None)
+            .map_err(|e| format!("Decompression error: {}", e))?;
+        
+        // The Adler-32 checksum is only verified on reaching the end of the stream
+        if status == Status::StreamEnd {
+            return Ok(true);
+        }
+        
+        // Running out of input without reaching the end means the checksum was never verified
+        if decoder.total_in() == in_before && decoder.total_out() == out_before {
+            return Err("Decompression error: truncated zlib stream".to_string());
+        }
+    }
+    
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // A loose object with an arbitrary header, compressed like a real one
+    fn crafted(header: &str, content: &[u8]) -> Vec<u8> {
+        compress_zlib(&[header.as_bytes(), content].concat()).unwrap()
+    }
+    
+    #[test]
+    fn inflates_a_well_formed_object() {
+        let (kind, content) = inflate_object(&crafted("blob 5\0", b"hello"), 1 << 20).unwrap();
+        assert_eq!(kind, "blob");
+        assert_eq!(content, b"hello");
+    }
+    
+    #[test]
+    fn declared_size_longer_than_content_is_rejected() {
+        let error = inflate_object(&crafted("blob 10\0", b"hello"), 1 << 20).unwrap_err();
+        assert_eq!(error, "size mismatch".into());
+    }
+    
+    #[test]
+    fn declared_size_shorter_than_content_is_rejected() {
+        let error = inflate_object(&crafted("blob 3\0", b"hello"), 1 << 20).unwrap_err();
+        assert_eq!(error, "size mismatch".into());
+    }
+    
+    #[test]
+    fn zlib_bomb_stops_at_the_declared_size() {
+        // 16 MiB of zeros compresses to a few kilobytes
+        let bomb = crafted("blob 10\0", &vec![0; 16 << 20]);
+        assert!(bomb.len() < 1 << 20);
+        
+        let mut decoder = Decompress::new(true);
+        let mut output = Vec::new();
+        assert!(!inflate_limited(&mut decoder, &bomb, &mut output, 18 + 1).unwrap());
+        assert_eq!(output.len(), 19);
+        assert!(output.capacity() < 1 << 20);
+        
+        assert_eq!(inflate_object(&bomb, u64::MAX).unwrap_err(), "size mismatch".into());
+    }
+    
+    #[test]
+    fn size_above_the_ceiling_is_refused_before_inflating() {
+        let error = inflate_object(&crafted("blob 1048577\0", b""), 1 << 20).unwrap_err();
+        assert_eq!(error, InflateError::TooLarge("blob".to_string(), 1048577));
+        
+        // At the ceiling itself the object is still read
+        let content = vec![b'x'; 1 << 20];
+        let (_, inflated) = inflate_object(&crafted("blob 1048576\0", &content), 1 << 20).unwrap();
+        assert_eq!(inflated, content);
+    }
+    
+    #[test]
+    fn malformed_headers_are_rejected() {
+        assert!(inflate_object(&crafted("blob 5", b""), 1 << 20).is_err());
+        assert!(inflate_object(&crafted("blob x\0", b""), 1 << 20).is_err());
+        assert!(inflate_object(&crafted("bolb 0\0", b""), 1 << 20).is_err());
+        assert!(inflate_object(&crafted("blob 99999999999999999999999\0", b""), u64::MAX).is_err());
+    }
+    
+    #[test]
+    fn truncated_zlib_stream_is_an_error() {
+        let compressed = compress_zlib(b"some data to compress").unwrap();
+        
+        // Cutting off the Adler-32 checksum, or more, never yields partial data
+        for cut in [1, 2, 4, 8] {
+            let error = decompress_zlib(&compressed[..compressed.len() - cut]).unwrap_err();
+            assert_eq!(error, "Decompression error: truncated zlib stream");
+        }
+        
+        let object = crafted("blob 5\0", b"hello");
+        let error = inflate_object(&object[..object.len() - 2], 1 << 20).unwrap_err();
+        assert_eq!(error, "Decompression error: truncated zlib stream".into());
+    }
+    
+    #[test]
+    fn bad_zlib_checksum_is_an_error() {
+        let mut object = crafted("blob 5\0", b"hello");
+        let last = object.len() - 1;
+        object[last] ^= 0xff;
+        assert!(matches!(inflate_object(&object, 1 << 20), Err(InflateError::Corrupt(_))));
+    }
+}
\ No newline at end of file
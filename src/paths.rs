@@ -0,0 +1,101 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+// Paths are stored in the index and in trees as raw bytes, exactly as the filesystem
+// returned them. Rakke relies on Unix file modes and ownership throughout, so like the
+// rest of the crate this only builds on Unix, where every such name is a valid path.
+
+// Raw bytes of a worktree path, for recording it in the index
+pub fn to_bytes(path: &Path) -> Result<Vec<u8>, String> {
+    Ok(path.as_os_str().as_bytes().to_vec())
+}
+
+// Worktree path for bytes recorded in the index or a tree
+pub fn from_bytes(bytes: &[u8]) -> Result<PathBuf, String> {
+    Ok(PathBuf::from(OsStr::from_bytes(bytes)))
+}
+
+// A path in a form where names differing only in case compare equal, for core.ignoreCase.
+// Unicode case folding only applies to names that are valid UTF-8; others fold ASCII case.
+pub fn fold_case(path: &[u8]) -> Vec<u8> {
+    match std::str::from_utf8(path) {
+        Ok(path) => path.to_lowercase().into_bytes(),
+        Err(_) => path.to_ascii_lowercase(),
+    }
+}
+
+// Quote a path the way git prints unusual names: "tab\there" or "caf\303\251".
+// Names without control characters, quotes or backslashes are returned unchanged; bytes
+// above 0x7f are only escaped when quote_path (core.quotePath, on by default) is set.
+pub fn quote_c_style(path: &[u8], quote_path: bool) -> Vec<u8> {
+    let needs_quoting = |b: u8| b < 0x20 || b == b'"' || b == b'\\' || b == 0x7f || (b >= 0x80 && quote_path);
+    if !path.iter().any(|&b| needs_quoting(b)) {
+        return path.to_vec();
+    }
+    
+    let mut quoted = vec![b'"'];
+    for &b in path {
+        match b {
+            0x07 => quoted.extend_from_slice(b"\\a"),
+            0x08 => quoted.extend_from_slice(b"\\b"),
+            b'\t' => quoted.extend_from_slice(b"\\t"),
+            b'\n' => quoted.extend_from_slice(b"\\n"),
+            0x0b => quoted.extend_from_slice(b"\\v"),
+            0x0c => quoted.extend_from_slice(b"\\f"),
+            b'\r' => quoted.extend_from_slice(b"\\r"),
+            b'"' => quoted.extend_from_slice(b"\\\""),
+            b'\\' => quoted.extend_from_slice(b"\\\\"),
+            b if needs_quoting(b) => quoted.extend_from_slice(format!("\\{:03o}", b).as_bytes()),
+            b => quoted.push(b),
+        }
+    }
+    quoted.push(b'"');
+    quoted
+}
+
+// Undo git's C-style quoting: "a\tb\303\251" becomes the raw bytes
+pub fn unquote_c_style(quoted: &[u8]) -> Option<Vec<u8>> {
+    let inner = quoted.strip_prefix(b"\"")?.strip_suffix(b"\"")?;
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut chars = inner.iter().copied();
+    
+    while let Some(b) = chars.next() {
+        if b == b'"' {
+            // An unescaped quote before the end is malformed
+            return None;
+        }
+        
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        
+        match chars.next()? {
+            b'a' => bytes.push(0x07),
+            b'b' => bytes.push(0x08),
+            b'f' => bytes.push(0x0c),
+            b'n' => bytes.push(b'\n'),
+            b'r' => bytes.push(b'\r'),
+            b't' => bytes.push(b'\t'),
+            b'v' => bytes.push(0x0b),
+            b'\\' => bytes.push(b'\\'),
+            b'"' => bytes.push(b'"'),
+            // Three octal digits encode a raw byte
+            first @ b'0'..=b'3' => {
+                let mut value = (first - b'0') as u32;
+                for _ in 0..2 {
+                    let digit = chars.next()?;
+                    if !(b'0'..=b'7').contains(&digit) {
+                        return None;
+                    }
+                    value = value * 8 + (digit - b'0') as u32;
+                }
+                bytes.push(value as u8);
+            }
+            _ => return None,
+        }
+    }
+    
+    Some(bytes)
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use crate::paths;
+
+// Read pathspecs for --pathspec-from-file, from a file or stdin when the name is "-".
+// Entries are raw bytes, since they name files that need not be valid UTF-8.
+pub fn read_pathspec_file(file: &Path, nul_separated: bool) -> Result<Vec<Vec<u8>>, String> {
+    let mut content = Vec::new();
+    
+    if file == Path::new("-") {
+        io::stdin().read_to_end(&mut content)
+            .map_err(|e| format!("could not read pathspecs from stdin: {}", e))?;
+    } else {
+        content = fs::read(file)
+            .map_err(|e| format!("could not open '{}' for reading: {}", file.display(), e))?;
+    }
+    
+    // NUL-separated entries are taken verbatim
+    if nul_separated {
+        return Ok(content.split(|&b| b == 0)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| entry.to_vec())
+            .collect());
+    }
+    
+    // Newline-separated entries may be C-style quoted, as git prints unusual paths
+    let mut pathspecs = Vec::new();
+    for line in content.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        
+        if line.starts_with(b"\"") {
+            let unquoted = paths::unquote_c_style(line)
+                .ok_or_else(|| format!("line is badly quoted: {}", String::from_utf8_lossy(line)))?;
+            pathspecs.push(unquoted);
+        } else {
+            pathspecs.push(line.to_vec());
+        }
+    }
+    
+    Ok(pathspecs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    
+    fn read(name: &str, content: &[u8], nul_separated: bool) -> Result<Vec<Vec<u8>>, String> {
+        let path = env::temp_dir().join(format!("rakke-pathspec-{}-{}", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let result = read_pathspec_file(&path, nul_separated);
+        let _ = fs::remove_file(&path);
+        result
+    }
+    
+    #[test]
+    fn nul_separated_entries_are_verbatim() {
+        let entries = read("nul", b"with\nnewline\0\"quoted\"\0caf\xe9\0\0trailing \0", true).unwrap();
+        assert_eq!(entries, [b"with\nnewline".to_vec(), b"\"quoted\"".to_vec(), b"caf\xe9".to_vec(), b"trailing ".to_vec()]);
+    }
+    
+    #[test]
+    fn newline_separated_entries_may_be_quoted() {
+        let entries = read("lines", b"plain\r\n\"tab\\there\"\n\ncaf\xe9", false).unwrap();
+        assert_eq!(entries, [b"plain".to_vec(), b"tab\there".to_vec(), b"caf\xe9".to_vec()]);
+        assert!(read("bad", b"\"unterminated\n", false).is_err());
+    }
+}
\ No newline at end of file
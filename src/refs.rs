@@ -0,0 +1,159 @@
+use std::fs;
+use std::io::Write;
+use crate::lockfile::LockedFile;
+use crate::objects;
+use crate::repository::Repository;
+
+// Check a reference name against git's check-ref-format rules
+pub fn is_valid_name(name: &str) -> bool {
+    // The name "@" alone is reserved, and "@{" introduces reflog syntax
+    if name.is_empty() || name == "@" || name.contains("@{") {
+        return false;
+    }
+    
+    // No double dots, which would be read as a revision range
+    if name.contains("..") {
+        return false;
+    }
+    
+    // No control characters, spaces or characters with special meaning in revisions
+    if name.chars().any(|c| c.is_ascii_control() || " ~^:?*[\\".contains(c)) {
+        return false;
+    }
+    
+    if name.ends_with('.') {
+        return false;
+    }
+    
+    // Check each slash-separated component; this also rejects leading, trailing and double slashes
+    name.split('/').all(|component| {
+        !component.is_empty()
+            && !component.starts_with('.')
+            && !component.ends_with(".lock")
+    })
+}
+
+// Check a branch name, which additionally may not be "HEAD" or look like an option
+pub fn is_valid_branch_name(name: &str) -> bool {
+    name != "HEAD" && !name.starts_with('-') && is_valid_name(&format!("refs/heads/{}", name))
+}
+
+// The branch HEAD points at (e.g. "refs/heads/master"), or None when HEAD is detached
+pub fn head_ref(repo: &Repository) -> Result<Option<String>, String> {
+    let content = fs::read_to_string(repo.git_path("HEAD"))
+        .map_err(|e| format!("Cannot read HEAD: {}", e))?;
+    
+    Ok(content.trim_end().strip_prefix("ref: ").map(|target| target.to_string()))
+}
+
+// Point HEAD at a branch, which need not exist yet (an unborn branch)
+pub fn set_head_ref(repo: &Repository, target: &str) -> Result<(), String> {
+    let mut lock = LockedFile::acquire(repo.git_path("HEAD"))
+        .map_err(|e| format!("cannot lock ref 'HEAD': {}", e))?;
+    writeln!(lock, "ref: {}", target)
+        .map_err(|e| format!("cannot update ref 'HEAD': {}", e))?;
+    lock.commit()
+}
+
+// Resolve a ref such as "HEAD" or "refs/heads/master" to an object hash;
+// None if it does not exist yet, like the branch of a fresh repository
+pub fn read_ref(repo: &Repository, name: &str) -> Result<Option<String>, String> {
+    let mut name = name.to_string();
+    
+    // Follow symbolic refs, with a limit in case they form a loop
+    for _ in 0..5 {
+        let value = match fs::read_to_string(repo.git_path(&name)) {
+            Ok(content) => content.trim_end().to_string(),
+            Err(_) => return read_packed_ref(repo, &name),
+        };
+        
+        match value.strip_prefix("ref: ") {
+            Some(target) => name = target.to_string(),
+            None if objects::is_valid_hash(&value) => return Ok(Some(value)),
+            None => return Err(format!("ref {} is corrupt: '{}'", name, value)),
+        }
+    }
+    
+    Err(format!("too many levels of symbolic refs at {}", name))
+}
+
+// Look a ref up in .git/packed-refs, where "<hash> <name>" lines hold refs that have no loose file
+fn read_packed_ref(repo: &Repository, name: &str) -> Result<Option<String>, String> {
+    let content = match fs::read_to_string(repo.git_path("packed-refs")) {
+        Ok(content) => content,
+        Err(_) => return Ok(None),
+    };
+    
+    Ok(content.lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+        .filter_map(|line| line.split_once(' '))
+        .find(|(_, ref_name)| *ref_name == name)
+        .map(|(hash, _)| hash.to_string()))
+}
+
+// Point a ref at a new object, provided it still has the value the caller last saw
+// (None meaning it must not exist yet), so concurrent updates are not lost
+pub fn update_ref(repo: &Repository, name: &str, new_hash: &str, old_hash: Option<&str>) -> Result<(), String> {
+    if name != "HEAD" && !is_valid_name(name) {
+        return Err(format!("refusing to update ref with bad name '{}'", name));
+    }
+    
+    let path = repo.git_path(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("cannot lock ref '{}': {}", name, e))?;
+    }
+    
+    // Check the current value while holding the lock, so nobody can change it in between
+    let mut lock = LockedFile::acquire(&path)
+        .map_err(|e| format!("cannot lock ref '{}': {}", name, e))?;
+    let current = read_ref(repo, name)?;
+    if current.as_deref() != old_hash {
+        return Err(match (current, old_hash) {
+            (Some(_), None) => format!("cannot lock ref '{}': reference already exists", name),
+            (Some(current), Some(old)) => format!("cannot lock ref '{}': is at {} but expected {}", name, current, old),
+            (None, _) => format!("cannot lock ref '{}': unable to resolve reference '{}'", name, name),
+        });
+    }
+    
+    writeln!(lock, "{}", new_hash)
+        .map_err(|e| format!("cannot update ref '{}': {}", name, e))?;
+    lock.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn invalid_names_are_rejected() {
+        for name in [
+            "", "@", "refs/heads/a..b", "refs/heads/a@{1}", "refs/heads/a b", "refs/heads/a~1", "refs/heads/a^",
+            "refs/heads/a:b", "refs/heads/a?", "refs/heads/a*", "refs/heads/a[b", "refs/heads/a\\b", "refs/heads/a\tb",
+            "refs/heads/a\x7f", "refs/heads/a.", "refs/heads/a.lock", "refs/heads/.hidden", "/refs/heads/a",
+            "refs/heads/a/", "refs/heads//a", "refs/heads/foo.lock/bar",
+        ] {
+            assert!(!is_valid_name(name), "{:?} should be invalid", name);
+        }
+    }
+    
+    #[test]
+    fn valid_edge_cases_are_accepted() {
+        for name in [
+            "refs/heads/master", "refs/heads/feature/foo", "refs/heads/a.b", "refs/heads/a@b", "refs/heads/@",
+            "refs/heads/locked", "refs/heads/x.lockx", "refs/tags/v1.0", "refs/heads/-dash", "refs/heads/caf\u{e9}",
+        ] {
+            assert!(is_valid_name(name), "{:?} should be valid", name);
+        }
+    }
+    
+    #[test]
+    fn branch_names_have_extra_rules() {
+        assert!(is_valid_branch_name("feature/foo"));
+        assert!(is_valid_branch_name("a.b"));
+        assert!(!is_valid_branch_name("HEAD"));
+        assert!(!is_valid_branch_name("-b"));
+        assert!(!is_valid_branch_name("feature/"));
+        assert!(!is_valid_branch_name("a..b"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::config::Config;
+use crate::lockfile;
+
+// Largest object rakke inflates into memory unless rakke.maxObjectSize says otherwise
+pub const DEFAULT_MAX_OBJECT_SIZE: u64 = 512 << 20;
+
+// A repository on disk: its git directory and, unless it is bare, the worktree around it.
+// Every file rakke touches is found through this, so the library never depends on the
+// current directory.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    git_dir: PathBuf,
+    work_tree: Option<PathBuf>,
+    max_object_size: u64, // Objects declaring a larger size are refused before inflating
+}
+
+impl Repository {
+    // Open the repository whose worktree root is the given directory
+    pub fn open<P: AsRef<Path>>(work_tree: P) -> Result<Repository, String> {
+        let work_tree = work_tree.as_ref().to_path_buf();
+        let git_dir = work_tree.join(".git");
+        if !git_dir.join("HEAD").is_file() || !git_dir.join("objects").is_dir() {
+            return Err("not a git repository (or any of the parent directories): .git".to_string());
+        }
+        
+        let mut repo = Repository::new(git_dir, Some(work_tree));
+        if let Some(limit) = Config::load(&repo)?.get_size("rakke.maxobjectsize") {
+            repo.max_object_size = limit;
+        }
+        Ok(repo)
+    }
+    
+    // Create a new repository in directory, or the git directory itself when bare
+    pub fn init<P: AsRef<Path>>(directory: P, bare: bool, initial_branch: &str) -> Result<Repository, String> {
+        let directory = directory.as_ref();
+        
+        // Create target directory if it doesn't exist
+        if !directory.exists() {
+            fs::create_dir_all(directory)
+                .map_err(|e| format!("Cannot create directory '{}': {}", directory.display(), e))?;
+        }
+        
+        let repo = if bare {
+            Repository::new(directory.to_path_buf(), None)
+        } else {
+            Repository::new(directory.join(".git"), Some(directory.to_path_buf()))
+        };
+        
+        // Check if repository already exists
+        if repo.git_path("HEAD").exists() {
+            return Err(format!("Reinitialization of existing Git repository in {}/", directory.display()));
+        }
+        
+        // Create git directory structure: objects, and refs for branches and tags
+        for dir in ["objects", "refs", "refs/heads", "refs/tags"] {
+            fs::create_dir_all(repo.git_path(dir))
+                .map_err(|e| format!("Cannot create {} directory: {}", dir, e))?;
+        }
+        
+        // Create HEAD file pointing to the initial branch
+        lockfile::write_locked(repo.git_path("HEAD"), format!("ref: refs/heads/{}\n", initial_branch).as_bytes())
+            .map_err(|e| format!("Cannot create HEAD file: {}", e))?;
+        
+        // Create basic configuration file
+        let mut config = format!("[core]\n\trepositoryformatversion = 0\n\tfilemode = true\n\tbare = {}\n", bare);
+        
+        // Probe for a case-insensitive filesystem by looking up the HEAD file in another case
+        if repo.git_path("hEaD").exists() {
+            config.push_str("\tignorecase = true\n");
+        }
+        
+        lockfile::write_locked(repo.git_path("config"), config.as_bytes())
+            .map_err(|e| format!("Cannot create config file: {}", e))?;
+        
+        // Create repository description file
+        lockfile::write_locked(
+            repo.git_path("description"),
+            b"Unnamed repository; edit this file 'description' to name the repository.\n",
+        )
+        .map_err(|e| format!("Cannot create description file: {}", e))?;
+        
+        Ok(repo)
+    }
+    
+    fn new(git_dir: PathBuf, work_tree: Option<PathBuf>) -> Repository {
+        Repository { git_dir, work_tree, max_object_size: DEFAULT_MAX_OBJECT_SIZE }
+    }
+    
+    // The .git directory, or the repository itself when bare
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+    
+    // Root of the checked-out files; bare repositories have none
+    pub fn work_tree(&self) -> Result<&Path, String> {
+        self.work_tree.as_deref()
+            .ok_or_else(|| "this operation must be run in a work tree".to_string())
+    }
+    
+    // A file inside the git directory, such as "HEAD" or "refs/heads/master"
+    pub fn git_path(&self, name: &str) -> PathBuf {
+        self.git_dir.join(name)
+    }
+    
+    // Largest object size, in bytes, that reading an object will inflate
+    pub fn max_object_size(&self) -> u64 {
+        self.max_object_size
+    }
+    
+    pub fn set_max_object_size(&mut self, limit: u64) -> &mut Self {
+        self.max_object_size = limit;
+        self
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use rakke::objects;
+use rakke::repository::Repository;
+
+// Diagnostic command checking the zlib and object round-trips on this platform
+pub fn execute(args: Vec<String>) {
+    if let Some(arg) = args.get(1) {
+        eprintln!("Unknown option: {}", arg);
+        eprintln!("usage: rakke selftest");
+        std::process::exit(129);
+    }
+    
+    let checks: Vec<(&str, Result<(), String>)> = vec![
+        ("zlib round-trip", check_zlib_round_trip()),
+        ("object write/read round-trip", check_object_round_trip()),
+    ];
+    
+    let mut failed = 0;
+    for (name, result) in &checks {
+        match result {
+            Ok(()) => println!("ok      {}", name),
+            Err(e) => {
+                println!("FAILED  {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    
+    if failed > 0 {
+        println!("{} of {} checks failed", failed, checks.len());
+        std::process::exit(1);
+    }
+    println!("all {} checks passed", checks.len());
+}
+
+fn expect(what: &str, actual: &str, expected: &str) -> Result<(), String> {
+    if actual != expected {
+        return Err(format!("{}: expected {}, got {}", what, expected, actual));
+    }
+    Ok(())
+}
+
+fn check_zlib_round_trip() -> Result<(), String> {
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let compressed = objects::compress_zlib(&data)?;
+    let decompressed = objects::decompress_zlib(&compressed)?;
+    if decompressed != data {
+        return Err("decompressed data differs from the original".to_string());
+    }
+    
+    // A stream missing its checksum must not be accepted
+    if objects::decompress_zlib(&compressed[..compressed.len() - 2]).is_ok() {
+        return Err("truncated stream was accepted".to_string());
+    }
+    Ok(())
+}
+
+// Write and read back an object in a scratch repository, leaving the current one untouched
+fn check_object_round_trip() -> Result<(), String> {
+    let scratch = scratch_dir();
+    let result = Repository::init(&scratch, true, "master")
+        .and_then(|repo| object_round_trip(&repo));
+    
+    // Always clean up, even when a check failed
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+fn object_round_trip(repo: &Repository) -> Result<(), String> {
+    let content = b"hello rakke\n";
+    let hash = objects::write_object(repo, "blob", content)?;
+    
+    // Same content as `echo 'hello rakke' | git hash-object --stdin`
+    expect("blob hash", &hash, "1760f42a70b4f77482f7743d2d1cab6d02347f77")?;
+    
+    let (kind, read_back) = objects::read_object(repo, &hash)?;
+    expect("object type", &kind, "blob")?;
+    if read_back != content {
+        return Err("object content differs after reading it back".to_string());
+    }
+    Ok(())
+}
+
+fn scratch_dir() -> PathBuf {
+    env::temp_dir().join(format!("rakke-selftest-{}", std::process::id()))
+}
\ No newline at end of file
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use rakke::config::Config;
+use rakke::paths;
+use rakke::repository::Repository;
+use rakke::wt_status::{self, Change, Status};
+
+pub fn execute(args: Vec<String>) {
+    let mut short = false;
+    
+    // Process arguments (skip "status" command itself)
+    for arg in &args[1..] {
+        match arg.as_str() {
+            // Porcelain v1 is the short format, which rakke never colours anyway
+            "-s" | "--short" | "--porcelain" => short = true,
+            "--long" => short = false,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            arg => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        }
+    }
+    
+    // Verify we are inside a git repository
+    let repo = match Repository::open(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    };
+    
+    let result = wt_status::collect_status(&repo).and_then(|status| {
+        // Paths are written as raw bytes, so non-UTF-8 names survive core.quotePath=false
+        let config = Config::load(&repo)?;
+        let quote_path = config.get_bool("core.quotepath").unwrap_or(true);
+        if short {
+            Ok(format_short(&status, quote_path))
+        } else {
+            let hints = config.get_bool("advice.statushints").unwrap_or(true);
+            Ok(format_long(&status, hints, quote_path))
+        }
+    });
+    
+    match result {
+        Ok(output) => {
+            // A closed pipe (e.g. "| head") is not worth an error message
+            let _ = io::stdout().write_all(&output);
+        }
+        Err(e) => {
+            eprintln!("fatal: {}", e);
+            std::process::exit(128);
+        }
+    }
+}
+
+fn change_label(change: Change) -> &'static str {
+    match change {
+        Change::Added => "new file:",
+        Change::Modified => "modified:",
+        Change::Deleted => "deleted:",
+    }
+}
+
+fn unmerged_label(code: &str) -> &'static str {
+    match code {
+        "DD" => "both deleted:",
+        "AU" => "added by us:",
+        "UD" => "deleted by them:",
+        "UA" => "added by them:",
+        "DU" => "deleted by us:",
+        "AA" => "both added:",
+        _ => "both modified:",
+    }
+}
+
+// "XY path" lines: X is the staged change, Y the unstaged one, "??" marks untracked files
+fn format_short(status: &Status, quote_path: bool) -> Vec<u8> {
+    let mut lines: BTreeMap<&[u8], String> = BTreeMap::new();
+    
+    for path in status.staged.keys().chain(status.unstaged.keys()) {
+        let x = status.staged.get(path).map_or(' ', |change| change.code());
+        let y = status.unstaged.get(path).map_or(' ', |change| change.code());
+        lines.insert(path, format!("{}{}", x, y));
+    }
+    for (path, code) in &status.unmerged {
+        lines.insert(path, code.to_string());
+    }
+    
+    let mut output = Vec::new();
+    let untracked = status.untracked.iter().map(|path| (path.as_slice(), "??".to_string()));
+    for (path, code) in lines.into_iter().chain(untracked) {
+        push_line(&mut output, &format!("{} ", code), &quote_short(path, quote_path));
+    }
+    output
+}
+
+// The short format also quotes names containing spaces, so that each line splits unambiguously
+fn quote_short(path: &[u8], quote_path: bool) -> Vec<u8> {
+    let quoted = paths::quote_c_style(path, quote_path);
+    if quoted == path && path.contains(&b' ') {
+        [b"\"", path, b"\""].concat()
+    } else {
+        quoted
+    }
+}
+
+// Git's default sectioned output, with its advice lines unless advice.statusHints is off
+fn format_long(status: &Status, hints: bool, quote_path: bool) -> Vec<u8> {
+    let mut output = Vec::new();
+    
+    match (&status.branch, &status.head) {
+        (Some(branch), _) => {
+            output.extend_from_slice(format!("On branch {}\n", branch.strip_prefix("refs/heads/").unwrap_or(branch)).as_bytes());
+        }
+        (None, Some(head)) => output.extend_from_slice(format!("HEAD detached at {}\n", &head[..7]).as_bytes()),
+        (None, None) => output.extend_from_slice(b"Not currently on any branch.\n"),
+    }
+    if status.head.is_none() {
+        output.extend_from_slice(b"\nNo commits yet\n\n");
+    }
+    
+    // While merging, unstaging would lose the merge result, so git offers the merge commands instead
+    if status.merging {
+        if status.unmerged.is_empty() {
+            output.extend_from_slice(b"All conflicts fixed but you are still merging.\n");
+            if hints {
+                output.extend_from_slice(b"  (use \"git commit\" to conclude merge)\n");
+            }
+        } else {
+            output.extend_from_slice(b"You have unmerged paths.\n");
+            if hints {
+                output.extend_from_slice(b"  (fix conflicts and run \"git commit\")\n");
+                output.extend_from_slice(b"  (use \"git merge --abort\" to abort the merge)\n");
+            }
+        }
+        output.push(b'\n');
+    }
+    
+    let unstage_hint = if status.merging {
+        None
+    } else if status.head.is_some() {
+        Some("  (use \"git restore --staged <file>...\" to unstage)\n")
+    } else {
+        Some("  (use \"git rm --cached <file>...\" to unstage)\n")
+    };
+    
+    if !status.staged.is_empty() {
+        output.extend_from_slice(b"Changes to be committed:\n");
+        if let (true, Some(hint)) = (hints, unstage_hint) {
+            output.extend_from_slice(hint.as_bytes());
+        }
+        push_changes(&mut output, &status.staged, quote_path);
+    }
+    
+    if !status.unmerged.is_empty() {
+        output.extend_from_slice(b"Unmerged paths:\n");
+        if hints {
+            let both_deleted = status.unmerged.values().any(|code| *code == "DD");
+            let deleted_one_side = status.unmerged.values().any(|code| matches!(*code, "AU" | "UA" | "UD" | "DU"));
+            output.extend_from_slice(unstage_hint.unwrap_or("").as_bytes());
+            output.extend_from_slice(match (both_deleted, deleted_one_side) {
+                (_, true) => b"  (use \"git add/rm <file>...\" as appropriate to mark resolution)\n".as_slice(),
+                (true, false) => b"  (use \"git rm <file>...\" to mark resolution)\n",
+                (false, false) => b"  (use \"git add <file>...\" to mark resolution)\n",
+            });
+        }
+        for (path, code) in &status.unmerged {
+            push_line(&mut output, &format!("\t{:<17}", unmerged_label(code)), &paths::quote_c_style(path, quote_path));
+        }
+        output.push(b'\n');
+    }
+    
+    if !status.unstaged.is_empty() {
+        output.extend_from_slice(b"Changes not staged for commit:\n");
+        if hints {
+            let verb = if status.unstaged.values().any(|change| *change == Change::Deleted) { "add/rm" } else { "add" };
+            output.extend_from_slice(format!("  (use \"git {} <file>...\" to update what will be committed)\n", verb).as_bytes());
+            output.extend_from_slice(b"  (use \"git restore <file>...\" to discard changes in working directory)\n");
+        }
+        push_changes(&mut output, &status.unstaged, quote_path);
+    }
+    
+    if !status.untracked.is_empty() {
+        output.extend_from_slice(b"Untracked files:\n");
+        if hints {
+            output.extend_from_slice(b"  (use \"git add <file>...\" to include in what will be committed)\n");
+        }
+        for path in &status.untracked {
+            push_line(&mut output, "\t", &paths::quote_c_style(path, quote_path));
+        }
+        output.push(b'\n');
+    }
+    
+    // A closing summary only when there is nothing staged to commit
+    let summary = if !status.staged.is_empty() {
+        None
+    } else if !status.unstaged.is_empty() || !status.unmerged.is_empty() {
+        Some(("no changes added to commit", " (use \"git add\" and/or \"git commit -a\")"))
+    } else if !status.untracked.is_empty() {
+        Some(("nothing added to commit but untracked files present", " (use \"git add\" to track)"))
+    } else if status.head.is_none() {
+        Some(("nothing to commit", " (create/copy files and use \"git add\" to track)"))
+    } else {
+        Some(("nothing to commit, working tree clean", ""))
+    };
+    
+    if let Some((summary, hint)) = summary {
+        output.extend_from_slice(summary.as_bytes());
+        if hints {
+            output.extend_from_slice(hint.as_bytes());
+        }
+        output.push(b'\n');
+    }
+    
+    output
+}
+
+// One "\t<label>   <path>" line per change, labels padded to line up like git's
+fn push_changes(output: &mut Vec<u8>, changes: &BTreeMap<Vec<u8>, Change>, quote_path: bool) {
+    for (path, change) in changes {
+        push_line(output, &format!("\t{:<12}", change_label(*change)), &paths::quote_c_style(path, quote_path));
+    }
+    output.push(b'\n');
+}
+
+fn push_line(output: &mut Vec<u8>, prefix: &str, path: &[u8]) {
+    output.extend_from_slice(prefix.as_bytes());
+    output.extend_from_slice(path);
+    output.push(b'\n');
+}
+
+fn print_help() {
+    println!("usage: rakke status [-s | --short | --porcelain | --long]");
+    println!();
+    println!("    -s, --short           show status concisely");
+    println!("    --porcelain           machine-readable output, same as --short");
+    println!("    --long                show status in long format (default)");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
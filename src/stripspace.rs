@@ -0,0 +1,85 @@
+use std::io::{self, Read};
+use rakke::config::Config;
+use rakke::message;
+use rakke::repository::Repository;
+
+// What to do besides cleaning up whitespace
+#[derive(PartialEq)]
+enum Mode {
+    Default,
+    StripComments,
+    CommentLines,
+}
+
+pub fn execute(args: Vec<String>) {
+    let mut mode = Mode::Default;
+    
+    // Process arguments (skip "stripspace" command itself)
+    for arg in &args[1..] {
+        let new_mode = match arg.as_str() {
+            "-s" | "--strip-comments" => Mode::StripComments,
+            "-c" | "--comment-lines" => Mode::CommentLines,
+            "--help" | "-h" => {
+                print_help();
+                return;
+            }
+            _ => {
+                eprintln!("Unknown option: {}", arg);
+                print_help();
+                std::process::exit(129);
+            }
+        };
+        
+        // The modes are mutually exclusive, but repeating one is fine
+        if mode != Mode::Default && mode != new_mode {
+            eprintln!("error: options '--strip-comments' and '--comment-lines' cannot be used together");
+            std::process::exit(129);
+        }
+        mode = new_mode;
+    }
+    
+    if let Err(e) = run(mode) {
+        eprintln!("fatal: {}", e);
+        std::process::exit(128);
+    }
+}
+
+fn run(mode: Mode) -> Result<(), String> {
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input)
+        .map_err(|e| format!("Cannot read from stdin: {}", e))?;
+    let text = String::from_utf8_lossy(&input);
+    
+    // Only the comment modes care about core.commentChar
+    let output = match mode {
+        Mode::Default => message::stripspace(&text, None),
+        Mode::StripComments => {
+            let comment = message::comment_char(&load_config()?)?;
+            message::stripspace(&text, Some(comment))
+        }
+        Mode::CommentLines => {
+            let comment = message::comment_char(&load_config()?)?;
+            message::comment_lines(&text, comment)
+        }
+    };
+    
+    print!("{}", output);
+    Ok(())
+}
+
+// Outside a repository only the global settings apply
+fn load_config() -> Result<Config, String> {
+    match Repository::open(".") {
+        Ok(repo) => Config::load(&repo),
+        Err(_) => Config::load_global(),
+    }
+}
+
+fn print_help() {
+    println!("usage: rakke stripspace [-s | --strip-comments]");
+    println!("   or: rakke stripspace [-c | --comment-lines]");
+    println!();
+    println!("    -s, --strip-comments  skip and remove all lines starting with comment character");
+    println!("    -c, --comment-lines   prepend comment character and space to each line");
+    println!("    -h, --help            show help");
+}
\ No newline at end of file
@@ -0,0 +1,485 @@
+use crate::config::Config;
+
+// Line prefixes git itself generates; they let a mixed block still count as trailers
+const GIT_GENERATED_PREFIXES: [&str; 2] = ["Signed-off-by: ", "(cherry picked from commit "];
+
+// A single "key: value" trailer with continuation lines unfolded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trailer {
+    pub key: String,
+    pub value: String,
+}
+
+// Where new trailers are placed relative to existing ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Where {
+    End,
+    Start,
+    After,
+    Before,
+}
+
+// What to do when a trailer with the same key already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IfExists {
+    AddIfDifferentNeighbor,
+    AddIfDifferent,
+    Add,
+    Replace,
+    DoNothing,
+}
+
+// What to do when no trailer with the same key exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IfMissing {
+    Add,
+    DoNothing,
+}
+
+// Placement policy for one trailer, resolved from config and command line
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    pub place: Where,
+    pub if_exists: IfExists,
+    pub if_missing: IfMissing,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            place: Where::End,
+            if_exists: IfExists::AddIfDifferentNeighbor,
+            if_missing: IfMissing::Add,
+        }
+    }
+}
+
+// One line of the trailer block; non-trailer lines are kept verbatim
+#[derive(Debug, Clone)]
+enum BlockLine {
+    Trailer { trailer: Trailer, raw: Vec<String> },
+    Other(String),
+}
+
+// A message split into body, trailer block and ignored comment tail
+struct Message {
+    body: Vec<String>,
+    block: Vec<BlockLine>,
+    tail: Vec<String>,
+    terminated: bool, // Whether the input ended with a newline
+}
+
+// Parse the trailers at the end of a commit message into key/value pairs
+pub fn parse_trailers(message: &str) -> Vec<Trailer> {
+    split_message(message, ":")
+        .block
+        .into_iter()
+        .filter_map(|line| match line {
+            BlockLine::Trailer { trailer, .. } => Some(trailer),
+            BlockLine::Other(_) => None,
+        })
+        .collect()
+}
+
+// Add trailers to a message, each with the command line overrides in effect when it was given
+pub fn add_trailers(message: &str, new_trailers: &[(Trailer, PolicyOverrides)], config: &Config) -> String {
+    let separators = config.get("trailer.separators").unwrap_or(":").to_string();
+    let mut parsed = split_message(message, &separators);
+    
+    for (trailer, overrides) in new_trailers {
+        let policy = resolve_policy(&trailer.key, config, overrides);
+        apply_trailer(&mut parsed.block, trailer, &policy);
+    }
+    
+    render_message(&parsed)
+}
+
+// Command line options that take precedence over trailer.* config
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolicyOverrides {
+    pub place: Option<Where>,
+    pub if_exists: Option<IfExists>,
+    pub if_missing: Option<IfMissing>,
+}
+
+// Parse a "--trailer" argument such as "Signed-off-by: Me" or "sign=Me"
+pub fn parse_trailer_argument(arg: &str, config: &Config) -> Result<Trailer, String> {
+    let separators = config.get("trailer.separators").unwrap_or(":").to_string();
+    
+    // The command line always accepts '=' in addition to the configured separators
+    let (token, value) = match arg.find(|c: char| c == '=' || separators.contains(c)) {
+        Some(pos) => (arg[..pos].trim(), arg[pos + 1..].trim()),
+        None => (arg.trim(), ""),
+    };
+    
+    if token.is_empty() {
+        return Err(format!("empty trailer token in trailer '{}'", arg));
+    }
+    
+    Ok(Trailer {
+        key: canonical_key(token, config),
+        value: value.to_string(),
+    })
+}
+
+// Map a token or its trailer.<alias>.key alias to the key written in messages
+fn canonical_key(token: &str, config: &Config) -> String {
+    for alias in config.subsections("trailer") {
+        let key = match config.get(&format!("trailer.{}.key", alias)) {
+            Some(key) => key.trim_end().trim_end_matches(':').trim_end(),
+            None => continue,
+        };
+        
+        if alias.eq_ignore_ascii_case(token) || key.eq_ignore_ascii_case(token) {
+            return key.to_string();
+        }
+    }
+    
+    token.to_string()
+}
+
+// Find the trailer.<alias> subsection configuring a given key, if any
+fn config_alias(key: &str, config: &Config) -> Option<String> {
+    config.subsections("trailer").into_iter().find(|alias| {
+        let configured_key = config.get(&format!("trailer.{}.key", alias))
+            .map(|k| k.trim_end().trim_end_matches(':').trim_end().to_string());
+        alias.eq_ignore_ascii_case(key)
+            || configured_key.is_some_and(|k| k.eq_ignore_ascii_case(key))
+    })
+}
+
+fn resolve_policy(key: &str, config: &Config, overrides: &PolicyOverrides) -> Policy {
+    let mut policy = Policy::default();
+    let alias = config_alias(key, config);
+    
+    // Per-key settings win over the global trailer.* defaults
+    let lookup = |name: &str| {
+        alias.as_ref()
+            .and_then(|a| config.get(&format!("trailer.{}.{}", a, name)))
+            .or_else(|| config.get(&format!("trailer.{}", name)))
+    };
+    
+    if let Some(place) = lookup("where").and_then(parse_where) {
+        policy.place = place;
+    }
+    if let Some(action) = lookup("ifexists").and_then(parse_if_exists) {
+        policy.if_exists = action;
+    }
+    if let Some(action) = lookup("ifmissing").and_then(parse_if_missing) {
+        policy.if_missing = action;
+    }
+    
+    // Command line options override everything
+    policy.place = overrides.place.unwrap_or(policy.place);
+    policy.if_exists = overrides.if_exists.unwrap_or(policy.if_exists);
+    policy.if_missing = overrides.if_missing.unwrap_or(policy.if_missing);
+    
+    policy
+}
+
+pub fn parse_where(value: &str) -> Option<Where> {
+    match value.to_lowercase().as_str() {
+        "end" => Some(Where::End),
+        "start" => Some(Where::Start),
+        "after" => Some(Where::After),
+        "before" => Some(Where::Before),
+        _ => None,
+    }
+}
+
+pub fn parse_if_exists(value: &str) -> Option<IfExists> {
+    match value.to_lowercase().as_str() {
+        "addifdifferentneighbor" => Some(IfExists::AddIfDifferentNeighbor),
+        "addifdifferent" => Some(IfExists::AddIfDifferent),
+        "add" => Some(IfExists::Add),
+        "replace" => Some(IfExists::Replace),
+        "donothing" => Some(IfExists::DoNothing),
+        _ => None,
+    }
+}
+
+pub fn parse_if_missing(value: &str) -> Option<IfMissing> {
+    match value.to_lowercase().as_str() {
+        "add" => Some(IfMissing::Add),
+        "donothing" => Some(IfMissing::DoNothing),
+        _ => None,
+    }
+}
+
+fn apply_trailer(block: &mut Vec<BlockLine>, trailer: &Trailer, policy: &Policy) {
+    // Positions of existing trailers sharing this key
+    let same_key: Vec<usize> = block.iter()
+        .enumerate()
+        .filter(|(_, line)| matches!(line, BlockLine::Trailer { trailer: t, .. } if t.key.eq_ignore_ascii_case(&trailer.key)))
+        .map(|(i, _)| i)
+        .collect();
+    
+    let new_line = BlockLine::Trailer { trailer: trailer.clone(), raw: Vec::new() };
+    
+    // Without an existing key, "after" and "before" degrade to "end" and "start"
+    if same_key.is_empty() {
+        if policy.if_missing == IfMissing::Add {
+            match policy.place {
+                Where::End | Where::After => block.push(new_line),
+                Where::Start | Where::Before => block.insert(0, new_line),
+            }
+        }
+        return;
+    }
+    
+    // The trailer we compare against and insert next to
+    let (neighbor, insert_at) = match policy.place {
+        Where::End => (last_trailer(block), block.len()),
+        Where::Start => (first_trailer(block), 0),
+        Where::After => (same_key.last().copied(), same_key[same_key.len() - 1] + 1),
+        Where::Before => (same_key.first().copied(), same_key[0]),
+    };
+    
+    let is_same = |index: usize| matches!(&block[index], BlockLine::Trailer { trailer: t, .. }
+        if t.key.eq_ignore_ascii_case(&trailer.key) && t.value == trailer.value);
+    
+    match policy.if_exists {
+        IfExists::DoNothing => {}
+        IfExists::Add => block.insert(insert_at, new_line),
+        IfExists::AddIfDifferent => {
+            if !same_key.iter().any(|&i| is_same(i)) {
+                block.insert(insert_at, new_line);
+            }
+        }
+        IfExists::AddIfDifferentNeighbor => {
+            if !neighbor.is_some_and(is_same) {
+                block.insert(insert_at, new_line);
+            }
+        }
+        IfExists::Replace => {
+            // The matching trailer closest to where the new one goes is dropped, and the new
+            // one placed as usual, so with "end" or "start" it moves rather than staying put
+            let target = match policy.place {
+                Where::End | Where::After => same_key[same_key.len() - 1],
+                Where::Start | Where::Before => same_key[0],
+            };
+            block.insert(insert_at, new_line);
+            block.remove(if insert_at <= target { target + 1 } else { target });
+        }
+    }
+}
+
+fn first_trailer(block: &[BlockLine]) -> Option<usize> {
+    block.iter().position(|line| matches!(line, BlockLine::Trailer { .. }))
+}
+
+fn last_trailer(block: &[BlockLine]) -> Option<usize> {
+    block.iter().rposition(|line| matches!(line, BlockLine::Trailer { .. }))
+}
+
+// Split a message into its body, trailing trailer block and ignored comment lines
+fn split_message(message: &str, separators: &str) -> Message {
+    let lines: Vec<String> = message.lines().map(|l| l.to_string()).collect();
+    let terminated = message.ends_with('\n');
+    
+    // Trailing blank and comment lines (e.g. a commit template) are not part of the message
+    let mut end = lines.len();
+    while end > 0 && (lines[end - 1].trim().is_empty() || lines[end - 1].starts_with('#')) {
+        end -= 1;
+    }
+    
+    // The first paragraph is the title and can never hold trailers
+    let title_end = lines[..end].iter()
+        .position(|l| l.trim().is_empty())
+        .unwrap_or(end);
+    
+    // The candidate block is the last paragraph after the title
+    let mut start = end;
+    while start > title_end && !lines[start - 1].trim().is_empty() {
+        start -= 1;
+    }
+    
+    let block = if start > title_end {
+        parse_block(&lines[start..end], separators)
+    } else {
+        None
+    };
+    
+    match block {
+        Some(block) => Message {
+            body: lines[..start].to_vec(),
+            block,
+            tail: lines[end..].to_vec(),
+            terminated,
+        },
+        None => Message {
+            body: lines[..end].to_vec(),
+            block: Vec::new(),
+            tail: lines[end..].to_vec(),
+            terminated,
+        },
+    }
+}
+
+// Parse a paragraph as a trailer block, or return None if it doesn't qualify as one
+fn parse_block(lines: &[String], separators: &str) -> Option<Vec<BlockLine>> {
+    let mut block: Vec<BlockLine> = Vec::new();
+    let mut trailer_lines = 0;
+    let mut non_trailer_lines = 0;
+    let mut recognized_prefix = false;
+    
+    for line in lines {
+        // Indented lines continue the previous trailer's value
+        if line.starts_with(char::is_whitespace) {
+            if let Some(BlockLine::Trailer { trailer, raw }) = block.last_mut() {
+                trailer.value = format!("{} {}", trailer.value, line.trim()).trim().to_string();
+                raw.push(line.clone());
+                continue;
+            }
+            non_trailer_lines += 1;
+            block.push(BlockLine::Other(line.clone()));
+            continue;
+        }
+        
+        if GIT_GENERATED_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+            recognized_prefix = true;
+        }
+        
+        match parse_trailer_line(line, separators) {
+            Some(trailer) => {
+                trailer_lines += 1;
+                block.push(BlockLine::Trailer { trailer, raw: vec![line.clone()] });
+            }
+            None if line.starts_with(GIT_GENERATED_PREFIXES[1]) => {
+                trailer_lines += 1;
+                block.push(BlockLine::Other(line.clone()));
+            }
+            None => {
+                non_trailer_lines += 1;
+                block.push(BlockLine::Other(line.clone()));
+            }
+        }
+    }
+    
+    // Either every line is a trailer, or git-generated trailers make up at least a quarter
+    let all_trailers = trailer_lines > 0 && non_trailer_lines == 0;
+    let mostly_trailers = recognized_prefix && trailer_lines * 3 >= non_trailer_lines;
+    
+    if all_trailers || mostly_trailers {
+        Some(block)
+    } else {
+        None
+    }
+}
+
+// Parse "Token: value", where the token is alphanumeric or '-' with optional spaces before the separator
+fn parse_trailer_line(line: &str, separators: &str) -> Option<Trailer> {
+    let mut whitespace_found = false;
+    
+    for (pos, c) in line.char_indices() {
+        if separators.contains(c) {
+            if pos == 0 {
+                return None;
+            }
+            return Some(Trailer {
+                key: line[..pos].trim_end().to_string(),
+                value: line[pos + c.len_utf8()..].trim().to_string(),
+            });
+        }
+        
+        if !whitespace_found && (c.is_ascii_alphanumeric() || c == '-') {
+            continue;
+        }
+        
+        if pos != 0 && c.is_whitespace() {
+            whitespace_found = true;
+            continue;
+        }
+        
+        return None;
+    }
+    
+    None
+}
+
+fn render_message(message: &Message) -> String {
+    let mut lines: Vec<String> = message.body.clone();
+    
+    // Like git, always leave a blank line before the trailer block position, even when
+    // no trailer ends up there; an unterminated last line is merely terminated
+    let needs_separator = lines.last().is_none_or(|l| !l.trim().is_empty());
+    let unterminated = !message.terminated && !lines.is_empty() && message.tail.is_empty();
+    if needs_separator && !unterminated {
+        lines.push(String::new());
+    }
+    
+    for line in &message.block {
+        match line {
+            BlockLine::Trailer { raw, .. } if !raw.is_empty() => lines.extend(raw.iter().cloned()),
+            BlockLine::Trailer { trailer, .. } => lines.push(format_trailer(trailer)),
+            BlockLine::Other(text) => lines.push(text.clone()),
+        }
+    }
+    
+    lines.extend(message.tail.iter().cloned());
+    
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+pub fn format_trailer(trailer: &Trailer) -> String {
+    if trailer.value.is_empty() {
+        format!("{}:", trailer.key)
+    } else {
+        format!("{}: {}", trailer.key, trailer.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer { key: key.to_string(), value: value.to_string() }
+    }
+    
+    fn add(message: &str, key: &str, value: &str, overrides: PolicyOverrides) -> String {
+        add_trailers(message, &[(trailer(key, value), overrides)], &Config::default())
+    }
+    
+    // Expected outputs below are what git interpret-trailers prints for the same input
+    #[test]
+    fn message_without_body_gets_a_new_block() {
+        assert!(parse_trailers("Subject\n").is_empty());
+        assert!(parse_trailers("Fixes: x\n").is_empty());
+        assert_eq!(add("Subject\n", "Signed-off-by", "A <a@b>", PolicyOverrides::default()), "Subject\n\nSigned-off-by: A <a@b>\n");
+    }
+    
+    #[test]
+    fn trailer_like_lines_in_the_body_are_not_trailers() {
+        let message = "Subject\n\nFixes: x\n\nMore text.\n";
+        assert!(parse_trailers(message).is_empty());
+        assert_eq!(add(message, "Acked-by", "A", PolicyOverrides::default()), "Subject\n\nFixes: x\n\nMore text.\n\nAcked-by: A\n");
+    }
+    
+    #[test]
+    fn folded_values_are_unfolded_but_kept_as_written() {
+        let message = "Subject\n\nKey: a\n  b\nOther: c\n";
+        assert_eq!(parse_trailers(message), [trailer("Key", "a b"), trailer("Other", "c")]);
+        assert_eq!(add(message, "New", "d", PolicyOverrides::default()), "Subject\n\nKey: a\n  b\nOther: c\nNew: d\n");
+    }
+    
+    #[test]
+    fn if_exists_and_if_missing_policies() {
+        let message = "Subject\n\nAcked-by: A\nTested-by: T\n";
+        let if_exists = |if_exists| PolicyOverrides { if_exists: Some(if_exists), ..Default::default() };
+        
+        assert_eq!(add(message, "Acked-by", "A", if_exists(IfExists::Replace)), "Subject\n\nTested-by: T\nAcked-by: A\n");
+        assert_eq!(add(message, "Acked-by", "A", if_exists(IfExists::DoNothing)), message);
+        assert_eq!(add(message, "Acked-by", "A", if_exists(IfExists::AddIfDifferent)), message);
+        assert_eq!(add(message, "Acked-by", "A", if_exists(IfExists::Add)), "Subject\n\nAcked-by: A\nTested-by: T\nAcked-by: A\n");
+        assert_eq!(add("Subject\n\nAcked-by: A\n", "Acked-by", "B", if_exists(IfExists::Replace)), "Subject\n\nAcked-by: B\n");
+        
+        // The neighbor compared against at the end is the last trailer, not the one with the key
+        assert_eq!(add(message, "Acked-by", "A", PolicyOverrides::default()), "Subject\n\nAcked-by: A\nTested-by: T\nAcked-by: A\n");
+        
+        let if_missing = PolicyOverrides { if_missing: Some(IfMissing::DoNothing), ..Default::default() };
+        assert_eq!(add("Subject\n\nAcked-by: A\n", "Reviewed-by", "C", if_missing), "Subject\n\nAcked-by: A\n");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use crate::config::Config;
+use crate::index::{self, Entry, Index};
+use crate::objects;
+use crate::paths;
+use crate::refs;
+use crate::repository::Repository;
+
+// How a path differs between HEAD and the index, or between the index and the worktree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl Change {
+    // Letter for the change in short status output
+    pub fn code(self) -> char {
+        match self {
+            Change::Added => 'A',
+            Change::Modified => 'M',
+            Change::Deleted => 'D',
+        }
+    }
+}
+
+// Everything status reports about a repository
+pub struct Status {
+    pub branch: Option<String>,                // Branch HEAD points at, None when detached
+    pub head: Option<String>,                  // Commit HEAD resolves to, None on an unborn branch
+    pub merging: bool,                         // A merge is waiting to be committed (MERGE_HEAD exists)
+    pub staged: BTreeMap<Vec<u8>, Change>,     // HEAD tree vs index
+    pub unstaged: BTreeMap<Vec<u8>, Change>,   // Index vs worktree
+    pub unmerged: BTreeMap<Vec<u8>, &'static str>, // Conflicted paths and their two-letter code
+    pub untracked: Vec<Vec<u8>>,               // Files and wholly untracked directories ("dir/")
+}
+
+// Compare HEAD, the index and the worktree
+pub fn collect_status(repo: &Repository) -> Result<Status, String> {
+    let branch = refs::head_ref(repo)?;
+    let head = refs::read_ref(repo, branch.as_deref().unwrap_or("HEAD"))?;
+    
+    // Files recorded in the HEAD commit, flattened to full paths
+    let mut head_files = BTreeMap::new();
+    if let Some(commit) = &head {
+        read_tree_files(repo, &objects::commit_tree_hash(repo, commit)?, b"", &mut head_files)?;
+    }
+    
+    let index_file = index::index_path(repo, env::var_os("GIT_INDEX_FILE"));
+    let index = Index::load(&index_file)?;
+    
+    // Entries modified in the same second the index was written can't be trusted by timestamp
+    let index_mtime = fs::metadata(&index_file).ok().map(|metadata| metadata.mtime() as u32);
+    
+    let mut staged = BTreeMap::new();
+    let mut unstaged = BTreeMap::new();
+    let mut stages: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+    
+    for entry in index.entries() {
+        if entry.stage != 0 {
+            stages.entry(entry.path.clone()).or_default().push(entry.stage);
+            continue;
+        }
+        
+        match head_files.get(&entry.path) {
+            None => {
+                staged.insert(entry.path.clone(), Change::Added);
+            }
+            Some((mode, hash)) if *mode != entry.mode || *hash != entry.hash => {
+                staged.insert(entry.path.clone(), Change::Modified);
+            }
+            Some(_) => {}
+        }
+        
+        if let Some(change) = worktree_change(repo, entry, index_mtime)? {
+            unstaged.insert(entry.path.clone(), change);
+        }
+    }
+    
+    // Files removed from the index since HEAD; conflicted paths are reported separately
+    for path in head_files.keys() {
+        if index.get(path).is_none() && !stages.contains_key(path) {
+            staged.insert(path.clone(), Change::Deleted);
+        }
+    }
+    
+    let unmerged = stages.into_iter()
+        .map(|(path, stages)| (path, unmerged_code(&stages)))
+        .collect();
+    
+    // Every path the index knows about, plus the directories that contain them; with
+    // core.ignoreCase a worktree name differing only in case still finds its entry
+    let ignore_case = Config::load(repo)?.get_bool("core.ignorecase").unwrap_or(false);
+    let tracked = Tracked::new(&index, ignore_case);
+    
+    let mut untracked = Vec::new();
+    collect_untracked(repo.work_tree()?, b"", &tracked, &mut untracked)?;
+    untracked.sort();
+    
+    let merging = repo.git_path("MERGE_HEAD").exists();
+    
+    Ok(Status { branch, head, merging, staged, unstaged, unmerged, untracked })
+}
+
+// Add the files of a tree and its subtrees to files as path -> (mode, hash)
+fn read_tree_files(repo: &Repository, tree: &str, prefix: &[u8], files: &mut BTreeMap<Vec<u8>, (u32, String)>) -> Result<(), String> {
+    let (kind, content) = objects::read_object(repo, tree)?;
+    if kind != "tree" {
+        return Err(format!("{} is a {}, not a tree", tree, kind));
+    }
+    
+    for entry in objects::parse_tree(&content)? {
+        let path = [prefix, &entry.name].concat();
+        if entry.mode == 0o040000 {
+            read_tree_files(repo, &entry.hash, &[&path, b"/".as_slice()].concat(), files)?;
+        } else {
+            files.insert(path, (entry.mode, entry.hash));
+        }
+    }
+    
+    Ok(())
+}
+
+// Compare an index entry with the worktree: stat data first, content only when that is inconclusive
+fn worktree_change(repo: &Repository, entry: &Entry, index_mtime: Option<u32>) -> Result<Option<Change>, String> {
+    let path = repo.work_tree()?.join(paths::from_bytes(&entry.path)?);
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(Some(Change::Deleted)),
+    };
+    
+    // Submodules are directories whose own state rakke doesn't inspect
+    if entry.mode == 0o160000 {
+        return Ok(if metadata.is_dir() { None } else { Some(Change::Deleted) });
+    }
+    if metadata.is_dir() {
+        return Ok(Some(Change::Deleted));
+    }
+    
+    let is_symlink = metadata.file_type().is_symlink();
+    if is_symlink != (entry.mode == 0o120000) {
+        return Ok(Some(Change::Modified));
+    }
+    
+    // A symlink's blob is its target
+    let content = if is_symlink {
+        let target = fs::read_link(&path)
+            .map_err(|e| format!("Cannot read symlink '{}': {}", path.display(), e))?;
+        paths::to_bytes(&target)?
+    } else {
+        // A size of 0 was smudged by a racy index write (or the file is empty): only the
+        // content can tell
+        let executable = metadata.permissions().mode() & 0o111 != 0;
+        let smudged = entry.size == 0;
+        if executable != (entry.mode == 0o100755) || (metadata.len() as u32 != entry.size && !smudged) {
+            return Ok(Some(Change::Modified));
+        }
+        
+        // Unchanged stat data is trusted unless the file may have changed after being indexed
+        let racy = index_mtime.is_none_or(|index_mtime| entry.mtime >= index_mtime);
+        if metadata.mtime() as u32 == entry.mtime && !racy && !smudged {
+            return Ok(None);
+        }
+        
+        fs::read(&path)
+            .map_err(|e| format!("Cannot read file '{}': {}", path.display(), e))?
+    };
+    
+    if objects::hash_object("blob", &content) == entry.hash {
+        Ok(None)
+    } else {
+        Ok(Some(Change::Modified))
+    }
+}
+
+// Two-letter code for a conflict, from which of base (1), ours (2) and theirs (3) are present
+fn unmerged_code(stages: &[u8]) -> &'static str {
+    match (stages.contains(&1), stages.contains(&2), stages.contains(&3)) {
+        (true, false, false) => "DD",
+        (false, true, false) => "AU",
+        (true, true, false) => "UD",
+        (false, false, true) => "UA",
+        (true, false, true) => "DU",
+        (false, true, true) => "AA",
+        _ => "UU",
+    }
+}
+
+// Tracked paths and their leading directories ("dir/"), case-folded under core.ignoreCase
+struct Tracked {
+    files: BTreeSet<Vec<u8>>,
+    dirs: BTreeSet<Vec<u8>>,
+    ignore_case: bool,
+}
+
+impl Tracked {
+    fn new(index: &Index, ignore_case: bool) -> Tracked {
+        let mut tracked = Tracked { files: BTreeSet::new(), dirs: BTreeSet::new(), ignore_case };
+        for entry in index.entries() {
+            let path = tracked.key(&entry.path);
+            for (pos, _) in path.iter().enumerate().filter(|(_, &b)| b == b'/') {
+                tracked.dirs.insert(path[..pos + 1].to_vec());
+            }
+            tracked.files.insert(path);
+        }
+        tracked
+    }
+    
+    fn key(&self, path: &[u8]) -> Vec<u8> {
+        if self.ignore_case {
+            paths::fold_case(path)
+        } else {
+            path.to_vec()
+        }
+    }
+    
+    fn has_file(&self, path: &[u8]) -> bool {
+        self.files.contains(&self.key(path))
+    }
+    
+    fn has_dir(&self, dir_path: &[u8]) -> bool {
+        self.dirs.contains(&self.key(dir_path))
+    }
+}
+
+// Find files the index doesn't know about; a directory holding no tracked files is listed as "dir/"
+fn collect_untracked(dir: &Path, prefix: &[u8], tracked: &Tracked, untracked: &mut Vec<Vec<u8>>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+    
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| format!("Cannot read directory entry: {}", e))?;
+        let path = [prefix, &paths::to_bytes(Path::new(&entry.file_name()))?].concat();
+        
+        // Skip .git directory and its contents, as add does
+        if path == b".git" {
+            continue;
+        }
+        
+        let file_type = entry.file_type()
+            .map_err(|e| format!("Cannot read file type of '{}': {}", entry.path().display(), e))?;
+        let dir_path = [&path, b"/".as_slice()].concat();
+        
+        if !file_type.is_dir() {
+            if !tracked.has_file(&path) {
+                untracked.push(path);
+            }
+        } else if tracked.has_file(&path) {
+            // A submodule checkout
+        } else if tracked.has_dir(&dir_path) {
+            collect_untracked(&entry.path(), &dir_path, tracked, untracked)?;
+        } else if contains_files(&entry.path()) {
+            // Empty directories can't be added, so they aren't worth mentioning
+            untracked.push(dir_path);
+        }
+    }
+    
+    Ok(())
+}
+
+fn contains_files(dir: &Path) -> bool {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    
+    entries.filter_map(|entry| entry.ok()).any(|entry| match entry.file_type() {
+        Ok(file_type) if file_type.is_dir() => contains_files(&entry.path()),
+        Ok(_) => true,
+        Err(_) => false,
+    })
+}
\ No newline at end of file
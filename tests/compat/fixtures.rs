@@ -0,0 +1,29 @@
+// Binds the shared repository fixtures to the rakke binary built for these tests
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Output;
+pub use rakke_fixtures::{assert_same, chown_tree, run_as, run_git, staged, staged_paths, succeed, RakkeRepo, TwinRepos};
+
+pub fn rakke() -> &'static Path {
+    Path::new(env!("CARGO_BIN_EXE_rakke"))
+}
+
+pub fn run_rakke<S: AsRef<OsStr>>(dir: &Path, args: &[S], stdin: &[u8]) -> Output {
+    rakke_fixtures::run(rakke(), dir, args, stdin)
+}
+
+pub fn twin_repos(name: &str) -> TwinRepos {
+    TwinRepos::new(rakke(), name)
+}
+
+pub fn rakke_repo(name: &str) -> RakkeRepo {
+    RakkeRepo::new(rakke(), name)
+}
+
+// Run the same command with both tools in the same directory and compare stdout
+pub fn assert_same_output(dir: &Path, args: &[&str], stdin: &[u8]) -> Vec<u8> {
+    let rakke = succeed(&format!("rakke {}", args.join(" ")), run_rakke(dir, args, stdin));
+    let git = succeed(&format!("git {}", args.join(" ")), run_git(dir, args, stdin));
+    assert_same(&args.join(" "), &rakke, &git);
+    rakke
+}
\ No newline at end of file
@@ -0,0 +1,218 @@
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::PermissionsExt;
+use crate::fixtures::{assert_same, chown_tree, rakke, rakke_repo, run_as, run_git, run_rakke, staged_paths, succeed, twin_repos};
+
+// The unprivileged user that runs commands which must not bypass permissions
+const NOBODY: u32 = 65534;
+
+#[test]
+#[ignore]
+fn git_reads_index_written_by_rakke() {
+    let repos = twin_repos("index-read");
+    
+    // Path lengths around the 8-byte entry padding boundary
+    let long_name = "x".repeat(30);
+    for path in ["a", "ab", "abcdefgh", "nested/dir/file.txt", &long_name] {
+        repos.write_file(path, path.as_bytes());
+    }
+    
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s"], b""));
+    assert_same("staged entries", &rakke, &git);
+    
+    // A tree built from rakke's index must match one built from git's
+    let rakke = succeed("git write-tree", run_git(&repos.rakke, &["write-tree"], b""));
+    let git = succeed("git write-tree", run_git(&repos.git, &["write-tree"], b""));
+    assert_same("tree from index", &rakke, &git);
+}
+
+#[test]
+#[ignore]
+fn staged_entries_survive_separate_adds() {
+    let repos = twin_repos("index-sequential");
+    for path in ["a.txt", "b.txt", "dir/c.txt"] {
+        repos.write_file(path, path.as_bytes());
+    }
+    
+    for path in ["a.txt", "b.txt", "dir"] {
+        succeed("rakke add", run_rakke(&repos.rakke, &["add", path], b""));
+    }
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s"], b""));
+    assert_same("staged entries", &rakke, &git);
+}
+
+#[test]
+#[ignore]
+fn rakke_reads_index_written_by_git() {
+    // Version 4 compresses paths; write-tree adds a TREE extension rakke has to skip
+    for version in ["2", "3", "4"] {
+        let repos = twin_repos(&format!("index-v{}", version));
+        let dir = &repos.git;
+        for path in ["a", "src/lib.rs", "src/main.rs", "src/sub/mod.rs"] {
+            repos.write_file(path, path.as_bytes());
+        }
+        
+        succeed("git add", run_git(dir, &["add", "."], b""));
+        succeed("git update-index", run_git(dir, &["update-index", "--index-version", version], b""));
+        succeed("git write-tree", run_git(dir, &["write-tree"], b""));
+        let before = succeed("git ls-files", run_git(dir, &["ls-files", "-s"], b""));
+        
+        // Re-adding an unchanged file must keep every other entry
+        succeed("rakke add", run_rakke(dir, &["add", "a"], b""));
+        let after = succeed("git ls-files", run_git(dir, &["ls-files", "-s"], b""));
+        assert_same(&format!("entries after rakke add on a version {} index", version), &after, &before);
+    }
+}
+
+#[test]
+#[ignore]
+fn add_reads_nul_separated_pathspec_file() {
+    let repos = twin_repos("pathspec-nul");
+    
+    // Separators that a newline-separated list could not carry unquoted
+    let names: [&[u8]; 4] = [b"with\nnewline", b"with space", b"caf\xe9", b"dir/\"quoted\""];
+    for name in names {
+        repos.write_file_bytes(name, name);
+    }
+    repos.write_file("not listed", b"left alone\n");
+    repos.write_file("list", &[names.join(&0u8), b"\0".to_vec()].concat());
+    
+    let args = ["add", "--pathspec-from-file=list", "--pathspec-file-nul"];
+    succeed("rakke add", run_rakke(&repos.rakke, &args, b""));
+    succeed("git add", run_git(&repos.git, &args, b""));
+    
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s", "-z"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s", "-z"], b""));
+    assert_same("staged entries", &rakke, &git);
+    assert_eq!(rakke.split(|&b| b == 0).filter(|entry| !entry.is_empty()).count(), names.len());
+    
+    // The same list on standard input
+    let stdin_repos = twin_repos("pathspec-nul-stdin");
+    for name in names {
+        stdin_repos.write_file_bytes(name, name);
+    }
+    let list = names.join(&0u8);
+    let args = ["add", "--pathspec-from-file=-", "--pathspec-file-nul"];
+    succeed("rakke add", run_rakke(&stdin_repos.rakke, &args, &list));
+    let from_stdin = succeed("git ls-files", run_git(&stdin_repos.rakke, &["ls-files", "-s", "-z"], b""));
+    assert_same("staged entries from stdin", &from_stdin, &git);
+}
+
+#[test]
+fn add_stages_exactly_the_nul_separated_list() {
+    let repo = rakke_repo("pathspec-nul-list");
+    let names: [&[u8]; 2] = [b"with\nnewline", b"with space"];
+    for name in names {
+        repo.write_file_bytes(name, name);
+    }
+    repo.write_file("not listed", b"left alone\n");
+    
+    let args = ["add", "--pathspec-from-file=-", "--pathspec-file-nul"];
+    succeed("rakke add", run_rakke(&repo.dir, &args, &names.join(&0u8)));
+    assert_same("staged paths", &staged_paths(&repo.dir), b"with\nnewline\nwith space\n");
+}
+
+#[test]
+#[ignore]
+fn add_accepts_absolute_paths_inside_the_worktree() {
+    let repos = twin_repos("add-absolute");
+    repos.write_file("dir/a", b"a\n");
+    repos.write_file("b", b"b\n");
+    
+    for repo in [&repos.rakke, &repos.git] {
+        let dir = repo.join("dir").to_str().unwrap().to_string();
+        let file = repo.join("b").to_str().unwrap().to_string();
+        let tool = if repo == &repos.rakke { run_rakke } else { run_git };
+        succeed("add", tool(repo, &["add", &dir, &file], b""));
+    }
+    
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s"], b""));
+    assert_same("staged entries", &rakke, &git);
+}
+
+#[test]
+fn add_resolves_absolute_paths_and_refuses_outside_ones() {
+    let repo = rakke_repo("add-outside");
+    repo.write_file("dir/a", b"a\n");
+    repo.write_file("b", b"b\n");
+    
+    let dir = repo.dir.join("dir").to_str().unwrap().to_string();
+    let file = repo.dir.join("b").to_str().unwrap().to_string();
+    succeed("rakke add", run_rakke(&repo.dir, &["add", &dir, &file], b""));
+    assert_same("staged paths", &staged_paths(&repo.dir), b"b\ndir/a\n");
+    
+    // A file beside the repository is refused, whether named absolutely or relatively
+    let outside = repo.dir.parent().unwrap().join("outside");
+    fs::write(&outside, b"outside\n").unwrap();
+    for path in [outside.to_str().unwrap(), "../outside"] {
+        let output = run_rakke(&repo.dir, &["add", path], b"");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("is outside repository"), "{:?}", output);
+    }
+    assert_same("staged paths after refused add", &staged_paths(&repo.dir), b"b\ndir/a\n");
+}
+
+#[test]
+fn overlapping_pathspecs_add_each_file_once() {
+    let repo = rakke_repo("add-overlap");
+    for path in ["top", "dir/a", "dir/sub/b"] {
+        repo.write_file(path, path.as_bytes());
+    }
+    
+    let output = run_rakke(&repo.dir, &["add", "--ignore-errors", ".", "dir", "dir/", "./dir/a", "dir/sub/../a"], b"");
+    succeed("rakke add", output.clone());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "added 3 files, 0 errors\n");
+    assert_same("staged paths", &staged_paths(&repo.dir), b"dir/a\ndir/sub/b\ntop\n");
+}
+
+#[test]
+fn unreadable_paths_are_counted_and_skipped() {
+    let repo = rakke_repo("add-unreadable");
+    let dir = &repo.dir;
+    for path in ["good", "locked", "dir/ok", "sealed/inner"] {
+        repo.write_file(path, path.as_bytes());
+    }
+    
+    fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+    fs::set_permissions(dir.join("sealed"), fs::Permissions::from_mode(0o000)).unwrap();
+    
+    // Permissions mean nothing to root, so there the repository and a copy of rakke are
+    // handed to nobody, who runs the commands instead
+    let as_root = fs::read(dir.join("locked")).is_ok();
+    let binary = dir.parent().unwrap().join("rakke-as-nobody");
+    if as_root {
+        fs::copy(rakke(), &binary).unwrap();
+        chown_tree(dir, NOBODY).unwrap();
+    }
+    let add = |args: &[&str]| if as_root { run_as(NOBODY, &binary, dir, args, b"") } else { Ok(run_rakke(dir, args, b"")) };
+    
+    // Without --ignore-errors the first failure aborts and nothing is staged
+    match add(&["add", "."]) {
+        Ok(output) => {
+            assert!(!output.status.success());
+            assert!(staged_paths(dir).is_empty());
+            
+            let output = add(&["add", "--ignore-errors", "."]).unwrap();
+            assert_eq!(output.status.code(), Some(1));
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(stderr.contains("error: unable to index file 'locked'"), "{}", stderr);
+            assert!(stderr.contains("sealed"), "{}", stderr);
+            assert!(stderr.ends_with("added 2 files, 2 errors\n"), "{}", stderr);
+            assert_same("staged files", &staged_paths(dir), b"dir/ok\ngood\n");
+        }
+        // Written past the test harness's capture, so the skip shows up in the output
+        Err(e) => writeln!(io::stderr(), "skipping unreadable_paths_are_counted_and_skipped: cannot run as nobody: {}", e).unwrap(),
+    }
+    
+    // Restore access so the repository can be removed
+    fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o644)).unwrap();
+    fs::set_permissions(dir.join("sealed"), fs::Permissions::from_mode(0o755)).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+// Compatibility tests for rakke. Checks of rakke's own behavior run by default; those
+// comparing it with real git need git on PATH, so they are ignored by default. Run
+// them with `cargo test --test compat -- --ignored`.
+mod fixtures;
+mod objects;
+mod index;
+mod messages;
+mod status;
\ No newline at end of file
@@ -0,0 +1,69 @@
+use crate::fixtures::{assert_same, assert_same_output, rakke_repo, run_git, run_rakke, succeed, twin_repos};
+
+const MESSAGE: &[u8] = b"\n\nSubject  \r\n\r\n\r\n# comment\nbody\t\n\n\nSigned-off-by: A <a@example.com>\n";
+
+#[test]
+#[ignore]
+fn stripspace_matches_git() {
+    let repos = twin_repos("stripspace");
+    
+    for args in [&["stripspace"][..], &["stripspace", "-s"], &["stripspace", "-c"]] {
+        assert_same_output(&repos.git, args, MESSAGE);
+    }
+}
+
+#[test]
+#[ignore]
+fn interpret_trailers_matches_git() {
+    let repos = twin_repos("trailers");
+    let message = b"Subject\n\nbody\n\nSigned-off-by: A <a@example.com>\n";
+    
+    let cases: [&[&str]; 4] = [
+        &["interpret-trailers", "--trailer", "Reviewed-by: B <b@example.com>"],
+        &["interpret-trailers", "--where", "start", "--trailer", "Fixes: 123"],
+        &["interpret-trailers", "--if-exists", "replace", "--trailer", "Signed-off-by: C <c@example.com>"],
+        &["interpret-trailers", "--parse"],
+    ];
+    for args in cases {
+        assert_same_output(&repos.git, args, message);
+    }
+}
+
+// Messages already ending in the committer's sign-off, or in someone else's
+const SIGNED: [&str; 3] = [
+    "Subject",
+    "Subject\n\nSigned-off-by: C O Mitter <committer@example.com>",
+    "Subject\n\nSigned-off-by: A <a@example.com>",
+];
+
+#[test]
+fn signoff_appends_the_committer() {
+    let repo = rakke_repo("signoff");
+    let mut messages = Vec::new();
+    for message in SIGNED {
+        succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-q", "-s", "--allow-empty", "-m", message], b""));
+        let commit = succeed("rakke cat-file", run_rakke(&repo.dir, &["cat-file", "-p", "HEAD"], b""));
+        let commit = String::from_utf8(commit).unwrap();
+        messages.push(commit.split_once("\n\n").unwrap().1.to_string());
+    }
+    
+    assert_eq!(messages, [
+        "Subject\n\nSigned-off-by: C O Mitter <committer@example.com>\n",
+        "Subject\n\nSigned-off-by: C O Mitter <committer@example.com>\n",
+        "Subject\n\nSigned-off-by: A <a@example.com>\nSigned-off-by: C O Mitter <committer@example.com>\n",
+    ]);
+}
+
+#[test]
+#[ignore]
+fn signoff_matches_git() {
+    let repos = twin_repos("signoff-git");
+    for message in SIGNED {
+        succeed("rakke commit", run_rakke(&repos.rakke, &["commit", "-q", "-s", "--allow-empty", "-m", message], b""));
+        succeed("git commit", run_git(&repos.git, &["commit", "-q", "-s", "--allow-empty", "-m", message], b""));
+    }
+    
+    let rakke = succeed("git log", run_git(&repos.rakke, &["log", "--format=%H%n%B"], b""));
+    let git = succeed("git log", run_git(&repos.git, &["log", "--format=%H%n%B"], b""));
+    assert_same("history", &rakke, &git);
+}
\ No newline at end of file
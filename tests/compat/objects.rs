@@ -0,0 +1,324 @@
+use std::fs;
+use crate::fixtures::{assert_same, assert_same_output, rakke_repo, run_git, run_rakke, succeed, twin_repos};
+
+// Hash of a worktree path as recorded in the index
+fn staged_hash(dir: &std::path::Path, path: &str) -> Vec<u8> {
+    succeed("git ls-files", run_git(dir, &["ls-files", "-s", "--", path], b""))
+}
+
+#[test]
+#[ignore]
+fn add_produces_the_same_blobs() {
+    let repos = twin_repos("add-blobs");
+    repos.write_file("empty", b"");
+    repos.write_file("text", b"hello\nworld\n");
+    repos.write_file("dir/binary", &(0..=255u8).collect::<Vec<_>>());
+    
+    // Sizes around SHA-1's 64-byte blocks, where header and content straddle the padding
+    let sizes = [47, 48, 55, 56, 64, 119, 120, 4096, 1 << 20];
+    for size in sizes {
+        repos.write_file(&format!("sized/{}", size), &vec![b'x'; size]);
+    }
+    
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    
+    let sized: Vec<String> = sizes.iter().map(|size| format!("sized/{}", size)).collect();
+    for path in ["empty", "text", "dir/binary"].into_iter().chain(sized.iter().map(String::as_str)) {
+        assert_same(path, &staged_hash(&repos.rakke, path), &staged_hash(&repos.git, path));
+        
+        // The staged blob must also be the one git hash-object computes for the file
+        let expected = succeed("git hash-object", run_git(&repos.rakke, &["hash-object", "--", path], b""));
+        let staged = String::from_utf8(staged_hash(&repos.rakke, path)).unwrap();
+        assert!(staged.contains(String::from_utf8(expected).unwrap().trim()), "{}: staged {}", path, staged.trim());
+    }
+}
+
+#[test]
+#[ignore]
+fn mktree_and_commit_tree_match_git() {
+    let repos = twin_repos("commit-tree");
+    let dir = &repos.git;
+    
+    // Blobs written by git, trees and commits by both tools in the same object store
+    let blob = succeed("git hash-object", run_git(dir, &["hash-object", "-w", "--stdin"], b"content\n"));
+    let blob = String::from_utf8(blob).unwrap();
+    let listing = format!(
+        "100644 blob {0}\tfile\n100755 blob {0}\ttool\n160000 commit {1}\tsub\n",
+        blob.trim(),
+        "5b1b3c1e5ab4dd5c3b8d4d1f0c2e6a7b8c9d0e1f" // Submodule commit not in this repository
+    );
+    let subtree = assert_same_output(dir, &["mktree"], listing.as_bytes());
+    
+    let listing = format!("040000 tree {}\tdir\n100644 blob {}\tdir.txt\n", String::from_utf8_lossy(&subtree).trim(), blob.trim());
+    let tree = assert_same_output(dir, &["mktree"], listing.as_bytes());
+    let tree = String::from_utf8(tree).unwrap();
+    
+    let root = assert_same_output(dir, &["commit-tree", tree.trim(), "-m", "initial"], b"");
+    let root = String::from_utf8(root).unwrap();
+    let child = assert_same_output(dir, &["commit-tree", tree.trim(), "-p", root.trim(), "-m", "subject", "-m", "body"], b"");
+    let child = String::from_utf8(child).unwrap();
+    
+    // A merge commit keeps both parents, in the order given
+    let merge = assert_same_output(dir, &["commit-tree", tree.trim(), "-p", child.trim(), "-p", root.trim(), "-m", "merge"], b"");
+    let merge = String::from_utf8(merge).unwrap();
+    let parents = succeed("git log", run_git(dir, &["log", "-1", "--format=%P", merge.trim()], b""));
+    assert_same("parents", &parents, format!("{} {}\n", child.trim(), root.trim()).as_bytes());
+    
+    // Abbreviated ids name the same objects, and a parent repeated in another spelling is dropped
+    assert_same_output(dir, &["commit-tree", &tree[..7], "-p", &child[..10], "-p", child.trim(), "-m", "short"], b"");
+}
+
+#[test]
+#[ignore]
+fn mktag_matches_git() {
+    let repos = twin_repos("mktag");
+    let dir = &repos.git;
+    
+    let blob = succeed("git hash-object", run_git(dir, &["hash-object", "-w", "--stdin"], b"tagged\n"));
+    let tag = format!(
+        "object {}type blob\ntag v1.0\ntagger T Agger <tagger@example.com> 1112911993 -0700\n\nrelease\n",
+        String::from_utf8(blob).unwrap()
+    );
+    assert_same_output(dir, &["mktag"], tag.as_bytes());
+}
+
+#[test]
+#[ignore]
+fn commit_produces_the_same_history() {
+    let repos = twin_repos("commit");
+    repos.write_file("README", b"readme\n");
+    repos.write_file("src/a.rs", b"a\n");
+    repos.write_file("src/a/b.rs", b"b\n");
+    repos.write_file("src-c", b"c\n");
+    
+    let steps: [&[&str]; 2] = [&["commit", "-q", "-m", "First  ", "-m", "body\n\n\n"], &["commit", "-q", "--allow-empty", "-m", "Empty"]];
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    for args in steps {
+        succeed("rakke commit", run_rakke(&repos.rakke, args, b""));
+        succeed("git commit", run_git(&repos.git, args, b""));
+    }
+    
+    let rakke = succeed("git log", run_git(&repos.rakke, &["log", "--format=%H %T %s"], b""));
+    let git = succeed("git log", run_git(&repos.git, &["log", "--format=%H %T %s"], b""));
+    assert_same("history", &rakke, &git);
+}
+
+#[test]
+#[ignore]
+fn cat_file_matches_git() {
+    let repos = twin_repos("cat-file");
+    repos.write_file("file", b"content\n");
+    repos.write_file("dir/tool", b"#!/bin/sh\n");
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    succeed("git commit", run_git(&repos.git, &["commit", "-q", "-m", "initial"], b""));
+    
+    let dir = &repos.git;
+    let tree = succeed("git rev-parse", run_git(dir, &["rev-parse", "HEAD:"], b""));
+    let tree = String::from_utf8(tree).unwrap();
+    let blob = succeed("git rev-parse", run_git(dir, &["rev-parse", "--short", "HEAD:file"], b""));
+    let blob = String::from_utf8(blob).unwrap();
+    
+    for name in ["HEAD", tree.trim(), blob.trim()] {
+        for option in ["-t", "-s", "-p"] {
+            assert_same_output(dir, &["cat-file", option, name], b"");
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn hash_object_matches_git() {
+    let repos = twin_repos("hash-object");
+    repos.write_file("file", b"content\n");
+    repos.write_file("binary", &(0..=255u8).rev().collect::<Vec<_>>());
+    let dir = &repos.git;
+    
+    assert_same_output(dir, &["hash-object", "file", "binary"], b"");
+    assert_same_output(dir, &["hash-object", "--stdin"], b"from stdin");
+    
+    // Other types are hashed the same way once they pass validation
+    let tag = b"object 5b1b3c1e5ab4dd5c3b8d4d1f0c2e6a7b8c9d0e1f\ntype commit\ntag v1\ntagger T <t@example.com> 0 +0000\n\nmsg\n";
+    assert_same_output(dir, &["hash-object", "-t", "tag", "--stdin"], tag);
+    
+    // Writing stores an object git can read back
+    let hash = succeed("rakke hash-object", run_rakke(dir, &["hash-object", "-w", "file"], b""));
+    let hash = String::from_utf8(hash).unwrap();
+    let content = succeed("git cat-file", run_git(dir, &["cat-file", "blob", hash.trim()], b""));
+    assert_same("file", &content, b"content\n");
+}
+
+#[test]
+#[ignore]
+fn commit_concludes_a_merge() {
+    let repos = twin_repos("merge");
+    repos.write_file("base", b"base\n");
+    repos.write_file("both", b"both\n");
+    
+    // Diverging branches, merged without committing so that MERGE_HEAD is left behind
+    let steps: [&[&str]; 8] = [
+        &["add", "."],
+        &["commit", "-q", "-m", "base"],
+        &["checkout", "-q", "-b", "side"],
+        &["commit", "-q", "--allow-empty", "-m", "side"],
+        &["checkout", "-q", "master"],
+        &["commit", "-q", "--allow-empty", "-m", "main"],
+        &["merge", "-q", "--no-commit", "--no-ff", "side"],
+        &["rev-parse", "--verify", "-q", "MERGE_HEAD"],
+    ];
+    for repo in [&repos.rakke, &repos.git] {
+        for args in steps {
+            succeed("git", run_git(repo, args, b""));
+        }
+    }
+    
+    succeed("rakke commit", run_rakke(&repos.rakke, &["commit", "-q", "-m", "Merge side"], b""));
+    succeed("git commit", run_git(&repos.git, &["commit", "-q", "-m", "Merge side"], b""));
+    let rakke = succeed("git log", run_git(&repos.rakke, &["log", "--format=%H %P %s"], b""));
+    let git = succeed("git log", run_git(&repos.git, &["log", "--format=%H %P %s"], b""));
+    assert_same("history", &rakke, &git);
+    assert!(!repos.rakke.join(".git/MERGE_HEAD").exists());
+    
+    // A conflicted merge can't be committed until the conflict is resolved
+    for (branch, content) in [("master", "ours\n"), ("side", "theirs\n")] {
+        succeed("git checkout", run_git(&repos.rakke, &["checkout", "-q", branch], b""));
+        repos.write_file("both", content.as_bytes());
+        succeed("git commit", run_git(&repos.rakke, &["commit", "-q", "-a", "-m", content], b""));
+    }
+    succeed("git checkout", run_git(&repos.rakke, &["checkout", "-q", "master"], b""));
+    assert!(!run_git(&repos.rakke, &["merge", "-q", "side"], b"").status.success());
+    
+    let output = run_rakke(&repos.rakke, &["commit", "-q", "-m", "Merge"], b"");
+    assert_eq!(output.status.code(), Some(128));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "fatal: Committing is not possible because you have unmerged files.\n");
+    assert!(repos.rakke.join(".git/MERGE_HEAD").exists());
+}
+
+#[test]
+#[ignore]
+fn orphan_branch_starts_a_new_history() {
+    let repos = twin_repos("orphan");
+    repos.write_file("file", b"content\n");
+    
+    // The first branch gets a commit; the orphan keeps the staged file but not the history
+    let steps: [&[&str]; 3] = [
+        &["commit", "-q", "-m", "first"],
+        &["checkout", "--orphan", "fresh"],
+        &["commit", "-q", "-m", "unrelated"],
+    ];
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    for args in steps {
+        succeed("rakke", run_rakke(&repos.rakke, args, b""));
+        succeed("git", run_git(&repos.git, args, b""));
+    }
+    
+    let parents = succeed("git log", run_git(&repos.rakke, &["log", "-1", "--format=%P", "fresh"], b""));
+    assert_same("orphan commit parents", &parents, b"\n");
+    let branches = succeed("git for-each-ref", run_git(&repos.rakke, &["for-each-ref", "--format=%(refname) %(objectname)"], b""));
+    let git = succeed("git for-each-ref", run_git(&repos.git, &["for-each-ref", "--format=%(refname) %(objectname)"], b""));
+    assert_same("branches", &branches, &git);
+    assert_same("HEAD", &succeed("git symbolic-ref", run_git(&repos.rakke, &["symbolic-ref", "HEAD"], b"")), b"refs/heads/fresh\n");
+}
+
+#[test]
+fn unborn_branch_is_reported_and_created_by_the_first_commit() {
+    let repo = rakke_repo("unborn");
+    let status = succeed("rakke status", run_rakke(&repo.dir, &["status"], b""));
+    assert_same("status", &status, b"On branch master\n\nNo commits yet\n\nnothing to commit (create/copy files and use \"git add\" to track)\n");
+    assert!(!repo.dir.join(".git/refs/heads/master").exists());
+    
+    // Nothing staged on an unborn branch is nothing to commit, not a failure to read HEAD
+    let output = run_rakke(&repo.dir, &["commit", "-m", "empty"], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("nothing to commit"), "{:?}", output);
+    
+    repo.write_file("file", b"content\n");
+    succeed("rakke add", run_rakke(&repo.dir, &["add", "file"], b""));
+    let summary = succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-m", "first"], b""));
+    assert!(String::from_utf8_lossy(&summary).starts_with("[master (root-commit) "), "{}", String::from_utf8_lossy(&summary));
+    assert!(repo.dir.join(".git/refs/heads/master").exists());
+}
+
+#[test]
+fn commit_tree_accepts_abbreviated_ids() {
+    let repo = rakke_repo("commit-tree-short");
+    let blob = succeed("rakke hash-object", run_rakke(&repo.dir, &["hash-object", "-w", "--stdin"], b"content\n"));
+    let listing = format!("100644 blob {}\tfile\n", String::from_utf8_lossy(&blob).trim());
+    let tree = succeed("rakke mktree", run_rakke(&repo.dir, &["mktree"], listing.as_bytes()));
+    let tree = String::from_utf8(tree).unwrap();
+    let root = succeed("rakke commit-tree", run_rakke(&repo.dir, &["commit-tree", &tree[..7], "-m", "root"], b""));
+    let root = String::from_utf8(root).unwrap();
+    
+    let child = run_rakke(&repo.dir, &["commit-tree", &tree[..7], "-p", &root[..8], "-p", root.trim(), "-m", "child"], b"");
+    assert_same("stderr", &child.stderr, format!("error: duplicate parent {} ignored\n", root.trim()).as_bytes());
+    let child = succeed("rakke commit-tree", child);
+    let commit = succeed("rakke cat-file", run_rakke(&repo.dir, &["cat-file", "-p", String::from_utf8_lossy(&child).trim()], b""));
+    let commit = String::from_utf8(commit).unwrap();
+    assert!(commit.starts_with(&format!("tree {}parent {}", tree, root)), "{}", commit);
+    
+    // Too short to be an abbreviation
+    let output = run_rakke(&repo.dir, &["commit-tree", &tree[..3], "-m", "short"], b"");
+    assert_eq!(output.status.code(), Some(128));
+}
+
+#[test]
+fn orphan_commit_has_no_parent() {
+    let repo = rakke_repo("orphan-parent");
+    repo.write_file("file", b"content\n");
+    let steps: [&[&str]; 4] = [
+        &["add", "."],
+        &["commit", "-q", "-m", "first"],
+        &["checkout", "--orphan", "fresh"],
+        &["commit", "-q", "-m", "unrelated"],
+    ];
+    for args in steps {
+        succeed("rakke", run_rakke(&repo.dir, args, b""));
+    }
+    
+    let commit = succeed("rakke cat-file", run_rakke(&repo.dir, &["cat-file", "-p", "refs/heads/fresh"], b""));
+    let commit = String::from_utf8(commit).unwrap();
+    assert!(commit.starts_with("tree ") && !commit.contains("\nparent "), "{}", commit);
+    assert_same("HEAD", &fs::read(repo.dir.join(".git/HEAD")).unwrap(), b"ref: refs/heads/fresh\n");
+}
+
+#[test]
+fn commit_records_merge_head_as_second_parent() {
+    let repo = rakke_repo("merge-head");
+    repo.write_file("file", b"first\n");
+    succeed("rakke add", run_rakke(&repo.dir, &["add", "."], b""));
+    succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-q", "-m", "first"], b""));
+    let first = fs::read_to_string(repo.dir.join(".git/refs/heads/master")).unwrap();
+    succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-q", "--allow-empty", "-m", "second"], b""));
+    let second = fs::read_to_string(repo.dir.join(".git/refs/heads/master")).unwrap();
+    
+    // A merge left to be concluded, as git merge --no-commit would leave it
+    fs::write(repo.dir.join(".git/MERGE_HEAD"), &first).unwrap();
+    fs::write(repo.dir.join(".git/MERGE_MSG"), b"Merge first\n").unwrap();
+    succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-q", "-m", "Merge first"], b""));
+    
+    let merge = succeed("rakke cat-file", run_rakke(&repo.dir, &["cat-file", "-p", "HEAD"], b""));
+    let parents: Vec<&str> = std::str::from_utf8(&merge).unwrap().lines().filter_map(|line| line.strip_prefix("parent ")).collect();
+    assert_eq!(parents, [second.trim(), first.trim()]);
+    assert!(!repo.dir.join(".git/MERGE_HEAD").exists());
+    assert!(!repo.dir.join(".git/MERGE_MSG").exists());
+}
+
+#[test]
+fn cat_file_streams_blobs_over_the_size_limit() {
+    let repo = rakke_repo("cat-file-large");
+    let content: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    repo.write_file("large", &content);
+    let hash = succeed("rakke hash-object", run_rakke(&repo.dir, &["hash-object", "-w", "large"], b""));
+    let hash = String::from_utf8(hash).unwrap();
+    
+    let mut config = fs::read(repo.dir.join(".git/config")).unwrap();
+    config.extend_from_slice(b"[rakke]\n\tmaxObjectSize = 1k\n");
+    fs::write(repo.dir.join(".git/config"), config).unwrap();
+    
+    let size = succeed("rakke cat-file -s", run_rakke(&repo.dir, &["cat-file", "-s", hash.trim()], b""));
+    assert_same("size", &size, b"100000\n");
+    let shown = succeed("rakke cat-file -p", run_rakke(&repo.dir, &["cat-file", "-p", hash.trim()], b""));
+    assert_same("content", &shown, &content);
+}
\ No newline at end of file
@@ -0,0 +1,201 @@
+// Repository fixtures shared by rakke's compatibility tests: scratch repositories made by
+// rakke alone or by rakke and git side by side, and helpers to run both tools reproducibly
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use rakke::index::Index;
+
+// A scratch directory for one test, removed again when dropped
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Scratch {
+        let root = env::temp_dir().join(format!("rakke-compat-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("cannot create fixture directory");
+        Scratch(root)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// A repository initialized by rakke alone, for checks that don't need git
+pub struct RakkeRepo {
+    _scratch: Scratch,
+    pub dir: PathBuf,
+}
+
+impl RakkeRepo {
+    // Initialize a repository with the given rakke binary
+    pub fn new(rakke: &Path, name: &str) -> RakkeRepo {
+        let scratch = Scratch::new(name);
+        let dir = scratch.0.join("rakke");
+        fs::create_dir(&dir).expect("cannot create rakke repository directory");
+        succeed("rakke init", run(rakke, &dir, &["init", "-b", "master"], b""));
+        RakkeRepo { _scratch: scratch, dir }
+    }
+    
+    pub fn write_file(&self, path: &str, content: &[u8]) {
+        self.write_file_bytes(path.as_bytes(), content);
+    }
+    
+    // Like write_file, for names that need not be valid UTF-8
+    pub fn write_file_bytes(&self, path: &[u8], content: &[u8]) {
+        write_fixture_file(&self.dir, path, content);
+    }
+}
+
+// A pair of freshly initialized repositories, one made by rakke and one by git,
+// for running the same operations side by side. Removed again when dropped.
+pub struct TwinRepos {
+    _scratch: Scratch,
+    pub rakke: PathBuf,
+    pub git: PathBuf,
+}
+
+impl TwinRepos {
+    // Initialize both repositories, the first with the given rakke binary
+    pub fn new(rakke: &Path, name: &str) -> TwinRepos {
+        let scratch = Scratch::new(name);
+        let repos = TwinRepos {
+            rakke: scratch.0.join("rakke"),
+            git: scratch.0.join("git"),
+            _scratch: scratch,
+        };
+        fs::create_dir(&repos.rakke).expect("cannot create rakke repository directory");
+        fs::create_dir(&repos.git).expect("cannot create git repository directory");
+        
+        succeed("rakke init", run(rakke, &repos.rakke, &["init", "-b", "master"], b""));
+        succeed("git init", run_git(&repos.git, &["init", "-q", "-b", "master"], b""));
+        repos
+    }
+    
+    // Write the same worktree file in both repositories
+    pub fn write_file(&self, path: &str, content: &[u8]) {
+        self.write_file_bytes(path.as_bytes(), content);
+    }
+    
+    // Like write_file, for names that need not be valid UTF-8
+    pub fn write_file_bytes(&self, path: &[u8], content: &[u8]) {
+        for repo in [&self.rakke, &self.git] {
+            write_fixture_file(repo, path, content);
+        }
+    }
+}
+
+fn write_fixture_file(repo: &Path, path: &[u8], content: &[u8]) {
+    let file = repo.join(OsStr::from_bytes(path));
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).expect("cannot create fixture directory");
+    }
+    fs::write(&file, content).expect("cannot write fixture file");
+}
+
+pub fn run_git<S: AsRef<OsStr>>(dir: &Path, args: &[S], stdin: &[u8]) -> Output {
+    run("git", dir, args, stdin)
+}
+
+// Run a program with a fixed identity and date and without the user's own configuration,
+// so both tools produce reproducible objects
+pub fn run<P: AsRef<OsStr>, S: AsRef<OsStr>>(program: P, dir: &Path, args: &[S], stdin: &[u8]) -> Output {
+    output(command(program, dir, args), stdin)
+}
+
+// Like run, but as another user, for checks that need file permissions enforced
+pub fn run_as<P: AsRef<OsStr>, S: AsRef<OsStr>>(uid: u32, program: P, dir: &Path, args: &[S], stdin: &[u8]) -> io::Result<Output> {
+    let mut command = command(program, dir, args);
+    command.uid(uid).gid(uid);
+    let mut child = command.spawn()?;
+    child.stdin.take().unwrap().write_all(stdin)?;
+    child.wait_with_output()
+}
+
+fn command<P: AsRef<OsStr>, S: AsRef<OsStr>>(program: P, dir: &Path, args: &[S]) -> Command {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join(".config"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_AUTHOR_NAME", "A U Thor")
+        .env("GIT_AUTHOR_EMAIL", "author@example.com")
+        .env("GIT_AUTHOR_DATE", "1112911993 -0700")
+        .env("GIT_COMMITTER_NAME", "C O Mitter")
+        .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+        .env("GIT_COMMITTER_DATE", "1112911993 -0700")
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_INDEX_FILE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    command
+}
+
+fn output(mut command: Command, stdin: &[u8]) -> Output {
+    let mut child = command.spawn().expect("cannot start command");
+    child.stdin.take().unwrap().write_all(stdin).expect("cannot write stdin");
+    child.wait_with_output().expect("cannot wait for command")
+}
+
+// Hand a directory tree over to another user
+pub fn chown_tree(path: &Path, uid: u32) -> io::Result<()> {
+    std::os::unix::fs::lchown(path, Some(uid), Some(uid))?;
+    if fs::symlink_metadata(path)?.is_dir() {
+        for entry in fs::read_dir(path)? {
+            chown_tree(&entry?.path(), uid)?;
+        }
+    }
+    Ok(())
+}
+
+// Require a command to succeed, returning its stdout
+pub fn succeed(what: &str, output: Output) -> Vec<u8> {
+    assert!(
+        output.status.success(),
+        "{} failed with {}\nstdout:\n{}\nstderr:\n{}",
+        what,
+        output.status,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+// Compare an artifact produced by both tools, showing both versions when they differ
+pub fn assert_same(what: &str, rakke: &[u8], git: &[u8]) {
+    assert!(
+        rakke == git,
+        "{} differs\n--- rakke ---\n{}\n--- git ---\n{}",
+        what,
+        String::from_utf8_lossy(rakke),
+        String::from_utf8_lossy(git)
+    );
+}
+
+// Staged paths as "git ls-files" lists them, read with rakke's own index parser
+pub fn staged_paths(repo: &Path) -> Vec<u8> {
+    let index = Index::load(repo.join(".git/index")).expect("cannot read index");
+    index.entries().flat_map(|entry| [entry.path.as_slice(), b"\n"].concat()).collect()
+}
+
+// Staged entries as "git ls-files -s" lists them
+pub fn staged(repo: &Path) -> Vec<u8> {
+    let index = Index::load(repo.join(".git/index")).expect("cannot read index");
+    let mut listing = Vec::new();
+    for entry in index.entries() {
+        listing.extend_from_slice(format!("{:06o} {} {}\t", entry.mode, entry.hash, entry.stage).as_bytes());
+        listing.extend_from_slice(&entry.path);
+        listing.push(b'\n');
+    }
+    listing
+}
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use crate::fixtures::{assert_same, assert_same_output, rakke_repo, run_git, run_rakke, staged, staged_paths, succeed, twin_repos, TwinRepos};
+
+// Compare long and short status output in the git repository
+fn assert_same_status(repos: &TwinRepos) {
+    for args in [&["status"][..], &["status", "--short"]] {
+        assert_same_output(&repos.git, args, b"");
+    }
+}
+
+#[test]
+#[ignore]
+fn status_matches_git() {
+    let repos = twin_repos("status");
+    let dir = &repos.git;
+    
+    // Unborn branch, first with nothing at all and then with untracked files
+    assert_same_status(&repos);
+    repos.write_file("README", b"readme\n");
+    repos.write_file("untracked/deep/file", b"new\n");
+    assert_same_status(&repos);
+    
+    succeed("git add", run_git(dir, &["add", "README"], b""));
+    assert_same_status(&repos);
+    succeed("git commit", run_git(dir, &["commit", "-q", "-m", "initial"], b""));
+    
+    // Staged, unstaged and untracked changes in the same directory
+    repos.write_file("README", b"changed\n");
+    repos.write_file("src/kept.rs", b"kept\n");
+    repos.write_file("src/gone.rs", b"gone\n");
+    succeed("git add", run_git(dir, &["add", "src"], b""));
+    fs::remove_file(dir.join("src/gone.rs")).unwrap();
+    repos.write_file("src/new.rs", b"new\n");
+    assert_same_status(&repos);
+    
+    succeed("git add", run_git(dir, &["add", "-A"], b""));
+    succeed("git commit", run_git(dir, &["commit", "-q", "-m", "second"], b""));
+    succeed("git rm", run_git(dir, &["rm", "-q", "--cached", "README"], b""));
+    assert_same_status(&repos);
+    
+    succeed("git add", run_git(dir, &["add", "README"], b""));
+    assert_same_status(&repos);
+}
+
+#[test]
+#[ignore]
+fn non_utf8_paths_match_git() {
+    let repos = twin_repos("non-utf8");
+    repos.write_file_bytes(b"caf\xe9", b"latin-1\n");
+    repos.write_file_bytes(b"caf\xc3\xa9", b"utf-8\n");
+    repos.write_file_bytes(b"dir\xff/tab\there", b"control\n");
+    repos.write_file("quote\"d", b"quoted\n");
+    
+    // A name that isn't UTF-8 can be given on the command line as well as found in a directory
+    let latin1 = OsStr::from_bytes(b"caf\xe9");
+    succeed("rakke add", run_rakke(&repos.rakke, &[OsStr::new("add"), latin1], b""));
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s"], b""));
+    assert_same("index", &rakke, &git);
+    let args = [OsStr::new("hash-object"), latin1];
+    let rakke = succeed("rakke hash-object", run_rakke(&repos.git, &args, b""));
+    let git = succeed("git hash-object", run_git(&repos.git, &args, b""));
+    assert_same("hash-object", &rakke, &git);
+    
+    // Staged names are quoted like git's, or passed through raw with core.quotePath off
+    repos.write_file_bytes(b"new\xe9", b"untracked\n");
+    assert_same_status(&repos);
+    succeed("git config", run_git(&repos.git, &["config", "core.quotePath", "false"], b""));
+    assert_same_status(&repos);
+    
+    succeed("rakke commit", run_rakke(&repos.rakke, &["commit", "-q", "-m", "bytes"], b""));
+    succeed("git commit", run_git(&repos.git, &["commit", "-q", "-m", "bytes"], b""));
+    let rakke = succeed("git rev-parse", run_git(&repos.rakke, &["rev-parse", "HEAD"], b""));
+    let git = succeed("git rev-parse", run_git(&repos.git, &["rev-parse", "HEAD"], b""));
+    assert_same("commit", &rakke, &git);
+}
+
+#[test]
+fn non_utf8_arguments_are_added() {
+    let repo = rakke_repo("non-utf8-argument");
+    repo.write_file_bytes(b"caf\xe9", b"latin-1\n");
+    repo.write_file_bytes(b"caf\xc3\xa9", b"utf-8\n");
+    
+    succeed("rakke add", run_rakke(&repo.dir, &[OsStr::new("add"), OsStr::from_bytes(b"caf\xe9")], b""));
+    assert_same("staged paths", &staged_paths(&repo.dir), b"caf\xe9\n");
+}
+
+#[test]
+#[ignore]
+fn symlinks_are_added_as_links() {
+    let repos = twin_repos("symlinks");
+    repos.write_file("dir/target", b"target\n");
+    for repo in [&repos.rakke, &repos.git] {
+        std::os::unix::fs::symlink("dir/target", repo.join("link")).unwrap();
+        std::os::unix::fs::symlink("dir", repo.join("dir-link")).unwrap();
+        std::os::unix::fs::symlink("missing", repo.join("dir/dangling")).unwrap();
+    }
+    
+    succeed("rakke add", run_rakke(&repos.rakke, &["add", "."], b""));
+    succeed("git add", run_git(&repos.git, &["add", "."], b""));
+    let rakke = succeed("git ls-files", run_git(&repos.rakke, &["ls-files", "-s"], b""));
+    let git = succeed("git ls-files", run_git(&repos.git, &["ls-files", "-s"], b""));
+    assert_same("index", &rakke, &git);
+    
+    // A freshly added link is clean in both tools' eyes, until it points elsewhere
+    let clean = succeed("rakke status", run_rakke(&repos.rakke, &["status", "--short"], b""));
+    assert_same("status after add", &clean, b"A  dir-link\nA  dir/dangling\nA  dir/target\nA  link\n");
+    fs::remove_file(repos.git.join("link")).unwrap();
+    std::os::unix::fs::symlink("dir/dangling", repos.git.join("link")).unwrap();
+    assert_same_status(&repos);
+}
+
+#[test]
+fn symlinks_are_staged_with_link_mode() {
+    let repo = rakke_repo("symlink-mode");
+    repo.write_file("dir/target", b"target\n");
+    std::os::unix::fs::symlink("dir/target", repo.dir.join("link")).unwrap();
+    std::os::unix::fs::symlink("missing", repo.dir.join("dangling")).unwrap();
+    succeed("rakke add", run_rakke(&repo.dir, &["add", "."], b""));
+    
+    let staged = String::from_utf8(staged(&repo.dir)).unwrap();
+    let modes: Vec<(&str, &str)> = staged.lines().map(|line| (&line[..6], line.split('\t').nth(1).unwrap())).collect();
+    assert_eq!(modes, [("120000", "dangling"), ("100644", "dir/target"), ("120000", "link")]);
+    let status = succeed("rakke status", run_rakke(&repo.dir, &["status", "--short"], b""));
+    assert_same("status after add", &status, b"A  dangling\nA  dir/target\nA  link\n");
+}
+
+#[test]
+#[ignore]
+fn ignore_case_finds_entries_in_another_case() {
+    let repos = twin_repos("ignorecase");
+    let dir = &repos.git;
+    repos.write_file("README", b"readme\n");
+    repos.write_file("Docs/guide", b"guide\n");
+    succeed("git add", run_git(dir, &["add", "."], b""));
+    succeed("git commit", run_git(dir, &["commit", "-q", "-m", "initial"], b""));
+    
+    // Renaming in place, as a case-insensitive filesystem would show it
+    fs::rename(dir.join("README"), dir.join("readme")).unwrap();
+    fs::rename(dir.join("Docs"), dir.join("docs")).unwrap();
+    repos.write_file("new", b"new\n");
+    assert_same_status(&repos);
+    
+    succeed("git config", run_git(dir, &["config", "core.ignoreCase", "true"], b""));
+    let status = assert_same_output(dir, &["status", "--short"], b"");
+    assert!(!String::from_utf8_lossy(&status).contains("readme"), "{}", String::from_utf8_lossy(&status));
+    assert_same_status(&repos);
+}
+
+#[test]
+fn ignore_case_keeps_entries_renamed_in_case() {
+    let repo = rakke_repo("ignorecase-rename");
+    repo.write_file("README", b"readme\n");
+    succeed("rakke add", run_rakke(&repo.dir, &["add", "."], b""));
+    succeed("rakke commit", run_rakke(&repo.dir, &["commit", "-q", "-m", "initial"], b""));
+    
+    fs::rename(repo.dir.join("README"), repo.dir.join("readme")).unwrap();
+    let mut config = fs::read(repo.dir.join(".git/config")).unwrap();
+    config.extend_from_slice(b"[core]\n\tignorecase = true\n");
+    fs::write(repo.dir.join(".git/config"), config).unwrap();
+    
+    // On a case-sensitive filesystem the old name is gone, but the new one isn't untracked
+    let status = succeed("rakke status", run_rakke(&repo.dir, &["status", "--short"], b""));
+    assert_same("status", &status, b" D README\n");
+}
+
+// Stage f, rewrite it with the same size in the second the index was written, then add g
+fn stage_racily(dir: &Path) {
+    fs::write(dir.join("f"), b"before\n").unwrap();
+    succeed("rakke add", run_rakke(dir, &["add", "f"], b""));
+    
+    fs::write(dir.join("f"), b"after!\n").unwrap();
+    let second = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1700000000);
+    for path in ["f", ".git/index"] {
+        fs::File::options().write(true).open(dir.join(path)).unwrap().set_modified(second).unwrap();
+    }
+    fs::write(dir.join("g"), b"g\n").unwrap();
+    succeed("rakke add", run_rakke(dir, &["add", "g"], b""));
+}
+
+#[test]
+fn change_in_the_same_second_as_add_is_seen() {
+    let repo = rakke_repo("racy");
+    stage_racily(&repo.dir);
+    let status = succeed("rakke status", run_rakke(&repo.dir, &["status", "--short"], b""));
+    assert_same("status", &status, b"AM f\nA  g\n");
+}
+
+#[test]
+#[ignore]
+fn git_sees_the_change_made_in_the_same_second_as_add() {
+    let repos = twin_repos("racy-git");
+    stage_racily(&repos.rakke);
+    let status = assert_same_output(&repos.rakke, &["status", "--short"], b"");
+    assert_same("status", &status, b"AM f\nA  g\n");
+}
\ No newline at end of file
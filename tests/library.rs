@@ -0,0 +1,324 @@
+// Drives the library API directly, without going through argv or the current directory
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use rakke::fsck;
+use rakke::ident::Ident;
+use rakke::index::{Entry, Index};
+use rakke::objects;
+use rakke::refs;
+use rakke::repository::Repository;
+use rakke::wt_status::{self, Change};
+
+// A scratch directory that is removed again when dropped
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Scratch {
+        let dir = env::temp_dir().join(format!("rakke-library-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Scratch(dir)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn ident() -> Ident {
+    Ident {
+        name: "A U Thor".to_string(),
+        email: "author@example.com".to_string(),
+        timestamp: 1700000000,
+        timezone: "+0000".to_string(),
+    }
+}
+
+#[test]
+fn commit_through_the_library() {
+    let scratch = Scratch::new("commit");
+    let cwd = env::current_dir().unwrap();
+    
+    let repo = Repository::init(&scratch.0, false, "main").unwrap();
+    assert_eq!(refs::head_ref(&repo).unwrap().as_deref(), Some("refs/heads/main"));
+    assert_eq!(refs::read_ref(&repo, "HEAD").unwrap(), None);
+    
+    // Stage a blob under a nested path and write the trees for it
+    let blob = objects::write_object(&repo, "blob", b"hello\n").unwrap();
+    assert_eq!(blob, "ce013625030ba8dba906f756967f9e9ca394464a");
+    
+    let mut index = Index::new();
+    index.add(Entry {
+        path: b"dir/hello.txt".to_vec(),
+        hash: blob.clone(),
+        mode: 0o100644,
+        size: 6,
+        mtime: 0,
+        stage: 0,
+    });
+    index.write(&repo, repo.git_path("index")).unwrap();
+    let index = Index::load(repo.git_path("index")).unwrap();
+    let tree = index.write_tree(&repo).unwrap();
+    
+    let content = objects::commit_content(&tree, &[], &ident(), &ident(), "Initial\n");
+    let commit = objects::write_object(&repo, "commit", &content).unwrap();
+    refs::update_ref(&repo, "refs/heads/main", &commit, None).unwrap();
+    
+    // Reopening by path finds the same history through HEAD
+    let reopened = Repository::open(&scratch.0).unwrap();
+    assert_eq!(refs::read_ref(&reopened, "HEAD").unwrap(), Some(commit.clone()));
+    
+    let (kind, content) = objects::read_object(&reopened, &commit).unwrap();
+    assert_eq!(kind, "commit");
+    assert!(content.starts_with(format!("tree {}\n", tree).as_bytes()));
+    
+    let (_, root) = objects::read_object(&reopened, &tree).unwrap();
+    let root = objects::parse_tree(&root).unwrap();
+    assert_eq!(root.len(), 1);
+    assert_eq!(root[0].name, b"dir");
+    
+    let (_, subtree) = objects::read_object(&reopened, &root[0].hash).unwrap();
+    let subtree = objects::parse_tree(&subtree).unwrap();
+    assert_eq!(subtree[0].name, b"hello.txt");
+    assert_eq!(subtree[0].hash, blob);
+    
+    // Nothing was written relative to the process's working directory
+    assert_eq!(env::current_dir().unwrap(), cwd);
+    assert!(scratch.0.join(".git/objects").join(&commit[..2]).join(&commit[2..]).is_file());
+}
+
+#[test]
+fn repositories_are_independent() {
+    let first = Scratch::new("first");
+    let second = Scratch::new("second");
+    let a = Repository::init(&first.0, false, "master").unwrap();
+    let b = Repository::init(&second.0, true, "master").unwrap();
+    assert!(b.work_tree().is_err());
+    
+    let hash = objects::write_object(&a, "blob", b"only in a\n").unwrap();
+    assert!(objects::read_object(&a, &hash).is_ok());
+    assert!(objects::read_object(&b, &hash).is_err());
+    assert_eq!(objects::find_object(&a, &hash[..7]).unwrap(), hash);
+    
+    // A second init of the same directory is refused
+    assert!(Repository::init(&first.0, false, "master").is_err());
+    assert!(Repository::open(env::temp_dir().join("rakke-library-missing")).is_err());
+}
+
+#[test]
+fn status_through_the_library() {
+    let scratch = Scratch::new("status");
+    let repo = Repository::init(&scratch.0, false, "master").unwrap();
+    fs::write(scratch.0.join("staged"), "staged\n").unwrap();
+    fs::write(scratch.0.join("untracked"), "untracked\n").unwrap();
+    
+    let hash = objects::hash_file(&scratch.0.join("staged"), "blob", Some(&repo)).unwrap();
+    let mut index = Index::new();
+    index.add(Entry { path: b"staged".to_vec(), hash, mode: 0o100644, size: 7, mtime: 0, stage: 0 });
+    index.write(&repo, repo.git_path("index")).unwrap();
+    
+    let status = wt_status::collect_status(&repo).unwrap();
+    assert_eq!(status.branch.as_deref(), Some("refs/heads/master"));
+    assert_eq!(status.head, None);
+    assert_eq!(status.staged.into_iter().collect::<Vec<_>>(), [(b"staged".to_vec(), Change::Added)]);
+    assert_eq!(status.untracked, [b"untracked".to_vec()]);
+    
+    // Once committed, the staged file matches HEAD
+    let tree = index.write_tree(&repo).unwrap();
+    let content = objects::commit_content(&tree, &[], &ident(), &ident(), "Initial\n");
+    let commit = objects::write_object(&repo, "commit", &content).unwrap();
+    refs::update_ref(&repo, "refs/heads/master", &commit, None).unwrap();
+    assert_eq!(objects::commit_tree_hash(&repo, &commit).unwrap(), tree);
+    
+    let status = wt_status::collect_status(&repo).unwrap();
+    assert_eq!(status.head, Some(commit));
+    assert!(status.staged.is_empty() && status.unstaged.is_empty());
+}
+
+#[test]
+fn connectivity_finds_missing_and_unreachable_objects() {
+    let scratch = Scratch::new("fsck");
+    let repo = Repository::init(&scratch.0, false, "master").unwrap();
+    let kept = objects::write_object(&repo, "blob", b"kept\n").unwrap();
+    let stray = objects::write_object(&repo, "blob", b"stray\n").unwrap();
+    let gone = objects::hash_object("blob", b"never written\n");
+    
+    // A commit whose tree names one stored blob and one that was never written
+    let mut entries = vec![
+        objects::TreeEntry { mode: 0o100644, name: b"kept".to_vec(), hash: kept.clone() },
+        objects::TreeEntry { mode: 0o100644, name: b"gone".to_vec(), hash: gone.clone() },
+    ];
+    let tree = objects::write_object(&repo, "tree", &objects::tree_content(&mut entries).unwrap()).unwrap();
+    let commit = objects::write_object(&repo, "commit", &objects::commit_content(&tree, &[], &ident(), &ident(), "Initial\n")).unwrap();
+    refs::update_ref(&repo, "refs/heads/master", &commit, None).unwrap();
+    
+    let connectivity = fsck::check_connectivity(&repo, &Index::new()).unwrap();
+    assert_eq!(connectivity.reachable, 3);
+    assert_eq!(connectivity.packed, 0);
+    assert_eq!(connectivity.missing, [gone]);
+    assert!(connectivity.corrupt.is_empty());
+    assert_eq!(connectivity.unreachable, [stray]);
+}
+
+#[test]
+fn objects_over_the_size_limit_are_streamed() {
+    let scratch = Scratch::new("streamed");
+    let mut repo = Repository::init(&scratch.0, false, "master").unwrap();
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let hash = objects::write_object(&repo, "blob", &content).unwrap();
+    
+    // Too large to read into memory, but the header and a stream are still available
+    repo.set_max_object_size(1000);
+    assert!(objects::read_object(&repo, &hash).is_err());
+    assert_eq!(objects::read_object_header(&repo, &hash).unwrap(), ("blob".to_string(), 200_000));
+    let mut streamed = Vec::new();
+    assert_eq!(objects::stream_object(&repo, &hash, &mut streamed).unwrap(), "blob");
+    assert_eq!(streamed, content);
+    
+    // A truncated object is an error rather than a short stream
+    let path = scratch.0.join(".git/objects").join(&hash[..2]).join(&hash[2..]);
+    let stored = fs::read(&path).unwrap();
+    fs::write(&path, &stored[..stored.len() / 2]).unwrap();
+    let error = objects::stream_object(&repo, &hash, &mut Vec::new()).unwrap_err();
+    assert!(error.to_string().contains("truncated zlib stream"), "{}", error);
+}
+
+#[test]
+fn racily_clean_entries_are_smudged() {
+    let scratch = Scratch::new("racy");
+    let repo = Repository::init(&scratch.0, false, "master").unwrap();
+    let mtime = UNIX_EPOCH + Duration::from_secs(1700000000);
+    
+    // Two files staged in the same second the index was written
+    let mut index = Index::new();
+    for (name, content) in [("changed", "before\n"), ("kept", "same\n")] {
+        let path = scratch.0.join(name);
+        fs::write(&path, content).unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(mtime).unwrap();
+        let hash = objects::write_object(&repo, "blob", content.as_bytes()).unwrap();
+        index.add(Entry { path: name.as_bytes().to_vec(), hash, mode: 0o100644, size: content.len() as u32, mtime: 1700000000, stage: 0 });
+    }
+    index.write(&repo, repo.git_path("index")).unwrap();
+    fs::File::options().write(true).open(repo.git_path("index")).unwrap().set_modified(mtime).unwrap();
+    
+    // One changes within that second, keeping its size, before an unrelated entry is staged
+    let changed = scratch.0.join("changed");
+    fs::write(&changed, "after!\n").unwrap();
+    fs::File::options().write(true).open(&changed).unwrap().set_modified(mtime).unwrap();
+    
+    let mut index = Index::load(repo.git_path("index")).unwrap();
+    let hash = objects::write_object(&repo, "blob", b"").unwrap();
+    index.add(Entry { path: b"other".to_vec(), hash, mode: 0o100644, size: 0, mtime: 1700000100, stage: 0 });
+    index.write(&repo, repo.git_path("index")).unwrap();
+    
+    // Its stat data can no longer be trusted once the index is newer than the change
+    let index = Index::load(repo.git_path("index")).unwrap();
+    assert_eq!(index.get(b"changed").unwrap().size, 0);
+    assert_eq!(index.get(b"kept").unwrap().size, 5);
+}
+
+// Parents of a commit, from its "parent" header lines
+fn parents(repo: &Repository, commit: &str) -> Vec<String> {
+    let (_, content) = objects::read_object(repo, commit).unwrap();
+    String::from_utf8(content).unwrap()
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix("parent "))
+        .map(|parent| parent.to_string())
+        .collect()
+}
+
+// Files whose names show an interrupted write: locks and temporary objects
+fn leftovers(dir: &Path, found: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if path.is_dir() {
+            leftovers(&path, found);
+        } else if name.ends_with(".lock") || name.starts_with("tmp_obj_") {
+            found.push(path);
+        }
+    }
+}
+
+#[test]
+fn concurrent_commits_and_branch_updates() {
+    const THREADS: usize = 8;
+    const COMMITS: usize = 10;
+    
+    let scratch = Scratch::new("concurrent");
+    let repo = Arc::new(Repository::init(&scratch.0, false, "master").unwrap());
+    
+    let workers: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let repo = Arc::clone(&repo);
+            thread::spawn(move || {
+                for n in 0..COMMITS {
+                    // Every thread also writes a blob the others write, racing on the same object
+                    let shared = objects::write_object(&repo, "blob", b"shared\n").unwrap();
+                    let own = objects::write_object(&repo, "blob", format!("{} {}\n", thread, n).as_bytes()).unwrap();
+                    
+                    let mut index = Index::new();
+                    for (path, hash) in [("shared", &shared), ("own", &own)] {
+                        index.add(Entry { path: path.as_bytes().to_vec(), hash: hash.clone(), mode: 0o100644, size: 0, mtime: 0, stage: 0 });
+                    }
+                    let tree = index.write_tree(&repo).unwrap();
+                    
+                    // Everyone commits onto master; a lost race means retrying on the new tip
+                    loop {
+                        let parent = refs::read_ref(&repo, "refs/heads/master").unwrap();
+                        let parent_list: Vec<String> = parent.iter().cloned().collect();
+                        let content = objects::commit_content(&tree, &parent_list, &ident(), &ident(), &format!("{} {}\n", thread, n));
+                        let commit = objects::write_object(&repo, "commit", &content).unwrap();
+                        match refs::update_ref(&repo, "refs/heads/master", &commit, parent.as_deref()) {
+                            Ok(()) => {
+                                // A branch of its own records the thread's latest commit
+                                let branch = format!("refs/heads/thread-{}", thread);
+                                let old = refs::read_ref(&repo, &branch).unwrap();
+                                refs::update_ref(&repo, &branch, &commit, old.as_deref()).unwrap();
+                                break;
+                            }
+                            Err(e) => assert!(e.starts_with("cannot lock ref"), "{}", e),
+                        }
+                    }
+                    
+                    // The index file is rewritten too, retrying while another thread holds its lock
+                    while index.write(&repo, repo.git_path("index")).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    
+    // Master's history is one unbroken chain holding every commit exactly once
+    let mut tip = refs::read_ref(&repo, "refs/heads/master").unwrap();
+    let mut seen = HashSet::new();
+    while let Some(commit) = tip {
+        assert!(seen.insert(commit.clone()), "commit {} appears twice", commit);
+        let mut commit_parents = parents(&repo, &commit);
+        assert!(commit_parents.len() <= 1);
+        tip = commit_parents.pop();
+    }
+    assert_eq!(seen.len(), THREADS * COMMITS);
+    
+    for thread in 0..THREADS {
+        let branch = refs::read_ref(&repo, &format!("refs/heads/thread-{}", thread)).unwrap().unwrap();
+        assert!(seen.contains(&branch));
+    }
+    assert_eq!(Index::load(repo.git_path("index")).unwrap().entries().count(), 2);
+    
+    let mut found = Vec::new();
+    leftovers(repo.git_dir(), &mut found);
+    assert!(found.is_empty(), "left behind: {:?}", found);
+}
\ No newline at end of file